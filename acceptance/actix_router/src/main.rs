@@ -0,0 +1,47 @@
+mod routes;
+mod v1;
+
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[utoipauto(paths = "./actix_router/src", router = "actix", prefixes = { "crate::v1" = "/api/v1" })]
+#[derive(OpenApi)]
+#[openapi(info(title = "Actix Router Test Api"))]
+pub(crate) struct ApiDoc;
+
+fn main() {
+    let _app = actix_web::App::new().configure(ApiDoc::configure);
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::{Method, StatusCode};
+    use actix_web::{test as actix_test, App};
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    async fn status(method: Method, uri: &str) -> StatusCode {
+        let app = actix_test::init_service(App::new().configure(ApiDoc::configure)).await;
+        let request = actix_test::TestRequest::default().method(method).uri(uri).to_request();
+        actix_test::call_service(&app, request).await.status()
+    }
+
+    #[actix_web::test]
+    async fn test_configure() {
+        assert_eq!(status(Method::GET, "/pets").await, StatusCode::OK);
+        assert_eq!(status(Method::POST, "/pets").await, StatusCode::OK);
+        assert_eq!(status(Method::GET, "/api/v1/pets/1").await, StatusCode::OK);
+        assert_eq!(status(Method::GET, "/pets/1").await, StatusCode::NOT_FOUND);
+        assert_eq!(status(Method::GET, "/internal").await, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_open_api() {
+        let api = ApiDoc::openapi();
+        let paths: Vec<&String> = api.paths.paths.keys().collect();
+        assert_eq!(paths, vec!["/pets", "/api/v1/pets/{id}"]);
+        assert_eq!(api.info.title, "Actix Router Test Api");
+    }
+}
@@ -0,0 +1,21 @@
+use actix_web::{get, post};
+use utoipauto::utoipa_ignore;
+
+#[utoipa::path(get, path = "/pets", responses((status = 200, description = "The pets")))]
+#[get("/pets")]
+pub async fn list_pets() -> &'static str {
+    "pets"
+}
+
+#[utoipa::path(post, path = "/pets", responses((status = 201, description = "The pet is created")))]
+#[post("/pets")]
+pub async fn create_pet() -> &'static str {
+    "created"
+}
+
+#[utoipa_ignore]
+#[utoipa::path(get, path = "/internal", responses((status = 200, description = "Internal")))]
+#[get("/internal")]
+pub async fn internal() -> &'static str {
+    "internal"
+}
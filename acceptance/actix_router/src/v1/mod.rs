@@ -0,0 +1,5 @@
+#[utoipa::path(get, path = "/pets/{id}", params(("id" = u64, Path)), responses((status = 200, description = "The pet")))]
+#[actix_web::get("/pets/{id}")]
+pub async fn get_pet() -> &'static str {
+    "pet"
+}
@@ -0,0 +1,81 @@
+mod routes;
+mod v1;
+mod v2;
+
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[utoipauto(paths = "./axum_router/src", router = "axum", prefixes = { "crate::v1" = "/api/v1", "crate::v2" = "/api/v2" })]
+#[derive(OpenApi)]
+#[openapi(info(title = "Axum Router Test Api"), modifiers(&OwnersPath))]
+pub(crate) struct ApiDoc;
+
+/// Describes the prefixed path
+pub struct OwnersPath;
+
+impl utoipa::Modify for OwnersPath {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(item) = openapi.paths.paths.get_mut("/api/v1/owners/{id}") {
+            item.summary = Some("An owner".to_string());
+        }
+    }
+}
+
+fn main() {
+    let (_router, api): (axum::Router, _) = ApiDoc::router().split_for_parts();
+    println!("Our OpenApi documentation {}", api.to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    #[test]
+    fn test_router() {
+        let (_router, api): (axum::Router, _) = ApiDoc::router().split_for_parts();
+        let paths: Vec<&String> = api.paths.paths.keys().collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/pets",
+                "/pets/{id}",
+                "/api/v1/owners/{id}",
+                "/api/v1/owners/{id}/debug",
+                "/api/v2/owners/{id}"
+            ]
+        );
+        let pets = &api.paths.paths["/pets"];
+        assert!(pets.get.is_some());
+        assert!(pets.post.is_some());
+        assert_eq!(api.info.title, "Axum Router Test Api");
+    }
+
+    #[test]
+    fn test_prefixed_path_item() {
+        let api = ApiDoc::openapi();
+        let owners = &api.paths.paths["/api/v1/owners/{id}"];
+        assert!(owners.get.is_some());
+        assert_eq!(owners.summary.as_deref(), Some("An owner"));
+        assert!(!api.paths.paths.contains_key("/owners/{id}"));
+    }
+
+    #[test]
+    fn test_same_path_in_two_prefixes() {
+        let api = ApiDoc::openapi();
+        let description = |path: &str| {
+            let operation = api.paths.paths[path].get.as_ref().unwrap();
+            operation.responses.responses["200"].clone()
+        };
+        let utoipa::openapi::RefOr::T(v1) = description("/api/v1/owners/{id}") else {
+            panic!("the response of v1 is inline");
+        };
+        let utoipa::openapi::RefOr::T(v2) = description("/api/v2/owners/{id}") else {
+            panic!("the response of v2 is inline");
+        };
+        assert_eq!(v1.description, "The owner");
+        assert_eq!(v2.description, "The owner, with its pets");
+        assert_eq!(api.paths.paths["/api/v2/owners/{id}"].summary, None);
+    }
+}
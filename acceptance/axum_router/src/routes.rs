@@ -0,0 +1,22 @@
+use utoipauto::utoipa_ignore;
+
+#[utoipa::path(get, path = "/pets", responses((status = 200, description = "The pets")))]
+pub async fn list_pets() -> &'static str {
+    "pets"
+}
+
+#[utoipa::path(post, path = "/pets", responses((status = 201, description = "The pet is created")))]
+pub async fn create_pet() -> &'static str {
+    "created"
+}
+
+#[utoipa::path(get, path = "/pets/{id}", params(("id" = u64, Path)), responses((status = 200, description = "The pet")))]
+pub async fn get_pet() -> &'static str {
+    "pet"
+}
+
+#[utoipa_ignore]
+#[utoipa::path(get, path = "/internal", responses((status = 200, description = "Internal")))]
+pub async fn internal() -> &'static str {
+    "internal"
+}
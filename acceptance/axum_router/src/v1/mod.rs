@@ -0,0 +1,10 @@
+#[utoipa::path(get, path = "/owners/{id}", params(("id" = u64, Path)), responses((status = 200, description = "The owner")))]
+pub async fn get_owner() -> &'static str {
+    "owner"
+}
+
+#[cfg(debug_assertions)]
+#[utoipa::path(get, path = "/owners/{id}/debug", params(("id" = u64, Path)), responses((status = 200, description = "The owner, with its internals")))]
+pub async fn debug_owner() -> &'static str {
+    "owner internals"
+}
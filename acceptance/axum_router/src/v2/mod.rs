@@ -0,0 +1,4 @@
+#[utoipa::path(get, path = "/owners/{id}", params(("id" = u64, Path)), responses((status = 200, description = "The owner, with its pets")))]
+pub async fn get_owner_v2() -> &'static str {
+    "owner and pets"
+}
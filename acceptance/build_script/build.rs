@@ -0,0 +1,9 @@
+use utoipauto_core::builder::Builder;
+
+fn main() {
+    // Explore the sources once per change, instead of on every compilation of the `#[utoipauto]` macro
+    Builder::new()
+        .root("src")
+        .generate("openapi_registry.rs")
+        .expect("failed to generate the openapi registry");
+}
@@ -0,0 +1,61 @@
+mod routes;
+
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[utoipauto(include = "openapi_registry.rs")]
+#[derive(OpenApi)]
+#[openapi(info(title = "Build Script Test Api"))]
+pub(crate) struct ApiDoc;
+
+/// The schemas are selected from the types referenced in the registry
+#[utoipauto(include = "openapi_registry.rs", schemas = "referenced")]
+#[derive(OpenApi)]
+#[openapi(info(title = "Build Script Referenced Test Api"))]
+pub(crate) struct ReferencedApiDoc;
+
+fn main() {
+    println!(
+        "Our OpenApi documentation {}",
+        ApiDoc::openapi().to_pretty_json().unwrap()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ApiDoc, ReferencedApiDoc};
+    use utoipa::OpenApi;
+
+    #[test]
+    fn test_open_api() {
+        let open_api = ApiDoc::openapi();
+        let paths: Vec<&String> = open_api.paths.paths.keys().collect();
+        assert_eq!(paths, vec!["/users", "/users/{id}"]);
+        let schemas = open_api.components.unwrap().schemas;
+        assert!(schemas.contains_key("User"));
+    }
+
+    #[test]
+    fn test_referenced_schemas() {
+        let components = ReferencedApiDoc::openapi().components.unwrap();
+        let schemas = components.schemas.keys().map(String::as_str).collect::<Vec<_>>();
+        // `User` is collected by utoipa from the response bodies, `UserFilter` is registered
+        assert_eq!(schemas, vec!["User", "UserFilter"]);
+    }
+
+    #[test]
+    fn test_report() {
+        let paths: Vec<&str> = ApiDoc::UTOIPAUTO_REPORT.iter().map(|item| item.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "crate::routes::list_users",
+                "crate::routes::get_user",
+                "crate::routes::User",
+                "crate::routes::UserFilter",
+                "crate::routes::InternalDto"
+            ]
+        );
+        assert_eq!(ApiDoc::UTOIPAUTO_REPORT[0].file, "src/routes.rs");
+    }
+}
@@ -0,0 +1,29 @@
+use utoipa::ToSchema;
+
+#[derive(ToSchema)]
+pub struct User {
+    pub id: u64,
+}
+
+#[derive(ToSchema)]
+pub enum UserFilter {
+    Active,
+    Archived,
+}
+
+/// Not referenced by any handler
+#[derive(ToSchema)]
+pub struct InternalDto {
+    pub secret: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/users",
+    params(("filter" = UserFilter, Query)),
+    responses((status = 200, body = Vec<User>))
+)]
+pub fn list_users() {}
+
+#[utoipa::path(get, path = "/users/{id}", responses((status = 200, body = User)))]
+pub fn get_user() {}
@@ -0,0 +1,108 @@
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[cfg(feature = "admin")]
+mod admin {
+    use utoipa::ToSchema;
+
+    #[derive(ToSchema)]
+    pub struct Role {
+        pub name: String,
+    }
+
+    #[utoipa::path(get, path = "/admin/roles", responses((status = 200, body = Vec<Role>)))]
+    pub fn list_roles() {}
+}
+
+#[cfg(feature = "audit")]
+mod audit {
+    use utoipa::openapi::{server::Server, OpenApi};
+
+    #[utoipa::path(get, path = "/audit/events")]
+    pub fn list_events() {}
+
+    #[utoipauto::modifier]
+    pub struct AuditServer;
+
+    impl utoipa::Modify for AuditServer {
+        fn modify(&self, openapi: &mut OpenApi) {
+            openapi.servers = Some(vec![Server::new("/audit")]);
+        }
+    }
+}
+
+#[cfg(feature = "billing")]
+mod billing {
+    #[utoipa::path(get, path = "/billing/invoices")]
+    pub fn list_invoices() {}
+}
+
+#[cfg(feature = "legacy")]
+mod legacy {
+    #[utoipa::path(get, path = "/legacy/users")]
+    pub fn list_users() {}
+}
+
+mod platform {
+    #[cfg(unix)]
+    #[utoipa::path(get, path = "/platform/unix")]
+    pub fn unix() {}
+
+    #[cfg(not(unix))]
+    #[utoipa::path(get, path = "/platform/other")]
+    pub fn other() {}
+
+    #[cfg(debug_assertions)]
+    #[utoipa::path(get, path = "/platform/debug")]
+    pub fn debug() {}
+}
+
+#[utoipa::path(get, path = "/health")]
+pub fn health() {}
+
+#[utoipauto(paths = "./cfg_items/src", warnings = "off", tags = "module", operation_id = "module_path")]
+#[derive(OpenApi)]
+#[openapi(info(title = "Cfg Items Test Api"))]
+pub struct ApiDoc;
+
+fn main() {
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    #[test]
+    fn test_enabled_predicates() {
+        let openapi = ApiDoc::openapi();
+        let mut paths = openapi.paths.paths.keys().map(String::as_str).collect::<Vec<_>>();
+        paths.sort();
+        let platform = match cfg!(unix) {
+            true => "/platform/unix",
+            false => "/platform/other",
+        };
+        let mut expected = vec!["/admin/roles", "/audit/events", "/health", "/platform/debug", platform];
+        expected.sort();
+        assert_eq!(paths, expected);
+
+        let schemas = openapi.components.unwrap().schemas.into_keys().collect::<Vec<_>>();
+        assert_eq!(schemas, vec!["Role"]);
+    }
+
+    #[test]
+    fn test_modifiers_of_enabled_predicates() {
+        let openapi = ApiDoc::openapi();
+        let operation = openapi.paths.paths["/admin/roles"].get.as_ref().unwrap();
+        assert_eq!(operation.tags.as_deref(), Some(&["admin".to_string()][..]));
+        assert_eq!(operation.operation_id.as_deref(), Some("admin_list_roles"));
+
+        let tags = openapi.tags.unwrap().into_iter().map(|tag| tag.name).collect::<Vec<_>>();
+        assert!(tags.contains(&"audit".to_string()));
+        assert!(!tags.contains(&"billing".to_string()));
+
+        assert_eq!(openapi.servers.unwrap()[0].url, "/audit");
+    }
+}
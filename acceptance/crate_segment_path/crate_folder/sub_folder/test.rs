@@ -1,13 +1,15 @@
 use utoipa::OpenApi;
 use utoipauto::utoipauto;
 
-#[utoipauto(paths = "( ./crate_segment_path/crate_folder/sub_folder/paths.rs from crate::sub_folder )")]
+#[utoipauto(paths = { path = "./crate_segment_path/crate_folder/sub_folder/paths.rs", crate = "crate::sub_folder" })]
 #[derive(OpenApi)]
 #[openapi(info(title = "Percentage API", version = "1.0.0"))]
 #[allow(dead_code)]
 pub struct CrateInAnotherPath {}
 
-#[utoipauto(paths = "( ./folder_in_src/crate_folder/new_sub_folder/paths.rs from folder-in-src::new_sub_folder )")]
+#[utoipauto(
+    paths = [{ path = "./folder_in_src/crate_folder/new_sub_folder/paths.rs", crate = "folder-in-src::new_sub_folder" }]
+)]
 #[derive(OpenApi)]
 #[openapi(info(title = "Percentage API", version = "1.0.0"))]
 #[allow(dead_code)]
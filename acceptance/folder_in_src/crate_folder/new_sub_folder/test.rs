@@ -1,7 +1,7 @@
 use utoipa::OpenApi;
 use utoipauto::utoipauto;
 
-#[utoipauto(paths = "( ./folder_in_src/crate_folder/new_sub_folder/paths.rs from crate::new_sub_folder )")]
+#[utoipauto(paths = { path = "./folder_in_src/crate_folder/new_sub_folder/paths.rs", crate = "crate::new_sub_folder" })]
 #[derive(OpenApi)]
 #[openapi(info(title = "Percentage API", version = "1.0.0"))]
 #[allow(dead_code)]
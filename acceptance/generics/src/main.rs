@@ -6,7 +6,8 @@ use routes::*;
 use utoipa::OpenApi;
 use utoipauto::utoipauto;
 
-#[utoipauto(paths = "./generics/src")]
+// The generic schemas are registered by the routes using them
+#[utoipauto(paths = "./generics/src", warnings = "off")]
 #[derive(Debug, OpenApi)]
 #[openapi(info(title = "Generic Test Api"))]
 pub(crate) struct ApiDoc;
@@ -0,0 +1,44 @@
+mod pets;
+
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[utoipa::path(get, path = "/health", responses((status = 200)))]
+pub fn health() {}
+
+#[utoipauto(discovery = "registry")]
+#[derive(OpenApi)]
+#[openapi(info(title = "Link Registry Test Api"), paths(health))]
+pub(crate) struct ApiDoc;
+
+fn main() {
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    #[test]
+    fn test_open_api() {
+        let api = ApiDoc::openapi();
+        let paths: Vec<&String> = api.paths.paths.keys().collect();
+        assert_eq!(paths, vec!["/health", "/pets", "/pets/{id}"]);
+
+        let components = api.components.unwrap();
+        let schemas: Vec<&String> = components.schemas.keys().collect();
+        assert_eq!(schemas, vec!["Owner", "Pet"]);
+        let responses: Vec<&String> = components.responses.keys().collect();
+        assert_eq!(responses, vec!["NotFound"]);
+    }
+
+    #[test]
+    fn test_stable_output() {
+        assert_eq!(
+            ApiDoc::openapi().to_json().unwrap(),
+            ApiDoc::openapi().to_json().unwrap()
+        );
+    }
+}
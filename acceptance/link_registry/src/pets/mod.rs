@@ -0,0 +1,27 @@
+use utoipa::{ToResponse, ToSchema};
+
+#[utoipauto::register]
+#[derive(ToSchema)]
+pub struct Pet {
+    pub id: u64,
+    pub owner: Owner,
+}
+
+#[utoipauto::register]
+#[derive(ToSchema)]
+pub struct Owner {
+    pub name: String,
+}
+
+#[utoipauto::register]
+#[derive(ToResponse)]
+#[response(description = "The pet doesn't exist")]
+pub struct NotFound;
+
+#[utoipauto::register]
+#[utoipa::path(get, path = "/pets/{id}", params(("id" = u64, Path)), responses((status = 200, body = Pet)))]
+pub fn get_pet() {}
+
+#[utoipauto::register]
+#[utoipa::path(post, path = "/pets", request_body = Pet, responses((status = 201)))]
+pub fn create_pet() {}
@@ -0,0 +1,46 @@
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[utoipa::path(get, path = "/health", responses((status = 200)))]
+pub fn health() {}
+
+#[utoipauto(paths = "./manifest_import/src", import = [manifest_lib])]
+#[derive(OpenApi)]
+#[openapi(info(title = "Manifest Import Test Api"))]
+pub struct ApiDoc;
+
+fn main() {
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    #[test]
+    fn test_open_api() {
+        let api = ApiDoc::openapi();
+        let paths: Vec<&String> = api.paths.paths.keys().collect();
+        // `credit_notes` is a default feature of the library, `refunds` isn't enabled
+        assert_eq!(paths, vec!["/health", "/invoices", "/invoices/credit-notes"]);
+        let components = api.components.unwrap();
+        let schemas: Vec<&String> = components.schemas.keys().collect();
+        assert_eq!(schemas, vec!["Invoice"]);
+    }
+
+    #[test]
+    fn test_report() {
+        let paths: Vec<&str> = ApiDoc::UTOIPAUTO_REPORT.iter().map(|item| item.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "crate::health",
+                "manifest_lib::invoices::list_invoices",
+                "manifest_lib::invoices::Invoice",
+                "manifest_lib::invoices::list_credit_notes"
+            ]
+        );
+    }
+}
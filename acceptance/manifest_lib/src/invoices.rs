@@ -0,0 +1,25 @@
+use utoipa::ToSchema;
+
+#[derive(ToSchema)]
+pub struct Invoice {
+    pub id: u64,
+}
+
+#[derive(ToSchema)]
+pub(crate) struct Draft {
+    pub id: u64,
+}
+
+#[utoipa::path(get, path = "/invoices", responses((status = 200, body = Vec<Invoice>)))]
+pub fn list_invoices() {}
+
+#[utoipa::path(get, path = "/invoices/drafts", responses((status = 200)))]
+pub(crate) fn list_drafts() {}
+
+#[cfg(feature = "refunds")]
+#[utoipa::path(post, path = "/invoices/{id}/refund", responses((status = 200)))]
+pub fn refund_invoice() {}
+
+#[cfg(feature = "credit_notes")]
+#[utoipa::path(get, path = "/invoices/credit-notes", responses((status = 200)))]
+pub fn list_credit_notes() {}
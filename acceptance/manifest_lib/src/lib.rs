@@ -0,0 +1,3 @@
+pub mod invoices;
+
+utoipauto::export_manifest!(paths = "./manifest_lib/src", warnings = "off");
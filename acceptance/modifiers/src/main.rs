@@ -0,0 +1,70 @@
+mod security;
+mod servers;
+
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[utoipauto(paths = "./modifiers/src")]
+#[derive(OpenApi)]
+#[openapi(info(title = "Modifiers Test Api"), modifiers(&Title))]
+pub(crate) struct ApiDoc;
+
+/// Listed by hand, it isn't added a second time
+pub struct Title;
+
+impl utoipa::Modify for Title {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.info.title.push('!');
+    }
+}
+
+/// Not marked, it isn't added: `modifiers(...)` couldn't take it by its name
+pub struct Prefixed(&'static str);
+
+impl utoipa::Modify for Prefixed {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.info.title.insert_str(0, self.0);
+    }
+}
+
+#[utoipa::path(get, path = "/pets")]
+pub fn get_pets() {}
+
+fn main() {
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    #[test]
+    fn test_discovered_modifier() {
+        let components = ApiDoc::openapi().components.unwrap();
+        assert!(components.security_schemes.contains_key("api_key"));
+    }
+
+    #[test]
+    fn test_existing_modifier() {
+        assert_eq!(ApiDoc::openapi().info.title, "Modifiers Test Api!");
+    }
+
+    #[test]
+    fn test_modifiers_order() {
+        let servers = ApiDoc::openapi().servers.unwrap();
+        let urls = servers.into_iter().map(|server| server.url).collect::<Vec<_>>();
+        assert_eq!(urls, vec!["http://localhost:8080/api/v1"]);
+    }
+
+    #[test]
+    fn test_expression_modifiers() {
+        let openapi = utoipauto::discover_openapi!(paths = "./modifiers/src");
+        assert!(openapi.paths.paths.contains_key("/pets"));
+        assert!(openapi.components.unwrap().security_schemes.contains_key("api_key"));
+        // `Title` is only listed by `ApiDoc`, it isn't marked
+        assert_eq!(openapi.info.title, "modifiers");
+        assert_eq!(openapi.servers.unwrap()[0].url, "http://localhost:8080/api/v1");
+    }
+}
@@ -0,0 +1,22 @@
+use utoipa::openapi::{OpenApi, Server};
+
+/// Depends on the servers added by `Servers`, which is discovered after it
+#[utoipauto::modifier(order = 2)]
+pub struct VersionedServers;
+
+impl utoipa::Modify for VersionedServers {
+    fn modify(&self, openapi: &mut OpenApi) {
+        for server in openapi.servers.iter_mut().flatten() {
+            server.url.push_str("/api/v1");
+        }
+    }
+}
+
+#[utoipauto::modifier(order = 1)]
+pub struct Servers;
+
+impl utoipa::Modify for Servers {
+    fn modify(&self, openapi: &mut OpenApi) {
+        openapi.servers.get_or_insert_with(Vec::new).push(Server::new("http://localhost:8080"));
+    }
+}
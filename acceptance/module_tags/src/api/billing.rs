@@ -0,0 +1,5 @@
+#[utoipa::path(get, path = "/invoices", responses((status = 200, description = "The invoices")))]
+pub fn list_invoices() {}
+
+#[utoipa::path(get, path = "/invoices/{id}", tag = "payments", params(("id" = u64, Path)), responses((status = 200, description = "The invoice")))]
+pub fn get_invoice() {}
@@ -0,0 +1,4 @@
+pub mod user_accounts;
+
+/// Invoices and payments
+pub mod billing;
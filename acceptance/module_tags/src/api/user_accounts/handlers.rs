@@ -0,0 +1,6 @@
+#[utoipa::path(get, path = "/users", responses((status = 200, description = "The users")))]
+pub fn list_users() {}
+
+#[cfg(test)]
+#[utoipa::path(delete, path = "/users/{id}", params(("id" = u64, Path)), responses((status = 204, description = "The user is deleted")))]
+pub fn delete_user() {}
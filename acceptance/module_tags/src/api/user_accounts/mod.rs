@@ -0,0 +1,3 @@
+//! Manage the user accounts
+
+pub mod handlers;
@@ -0,0 +1,54 @@
+mod api;
+
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[utoipauto(paths = "./module_tags/src", tags = { from = "module", depth = 2, case = "kebab" })]
+#[derive(OpenApi)]
+#[openapi(info(title = "Module Tags Test Api"))]
+pub(crate) struct ApiDoc;
+
+fn main() {
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    fn tags(path: &str, method: &str) -> serde_json::Value {
+        let openapi = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        openapi["paths"][path][method]["tags"].clone()
+    }
+
+    #[test]
+    fn test_module_tags() {
+        assert_eq!(tags("/users", "get"), serde_json::json!(["user-accounts"]));
+        assert_eq!(tags("/users/{id}", "delete"), serde_json::json!(["user-accounts"]));
+        assert_eq!(tags("/invoices", "get"), serde_json::json!(["billing"]));
+    }
+
+    #[test]
+    fn test_explicit_tag() {
+        assert_eq!(tags("/invoices/{id}", "get"), serde_json::json!(["payments"]));
+    }
+
+    #[test]
+    fn test_openapi_tags() {
+        let tags = ApiDoc::openapi()
+            .tags
+            .unwrap()
+            .into_iter()
+            .map(|tag| (tag.name, tag.description))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            vec![
+                ("user-accounts".to_string(), Some("Manage the user accounts".to_string())),
+                ("billing".to_string(), Some("Invoices and payments".to_string())),
+            ]
+        );
+    }
+}
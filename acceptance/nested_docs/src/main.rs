@@ -0,0 +1,43 @@
+mod orders;
+mod users;
+
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[utoipauto(paths = "./nested_docs/src")]
+#[derive(OpenApi)]
+#[openapi(info(title = "Nested Docs Test Api"))]
+pub(crate) struct ApiDoc;
+
+#[utoipa::path(get, path = "/health")]
+pub fn health() {}
+
+fn main() {
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    #[test]
+    fn test_nested_paths() {
+        let paths = ApiDoc::openapi().paths.paths.into_keys().collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/health", "/api/orders/", "/users/", "/users/{id}"]);
+    }
+
+    #[test]
+    fn test_nested_handlers_report() {
+        let nested = ApiDoc::UTOIPAUTO_REPORT
+            .iter()
+            .filter(|item| item.skip_reason == Some(utoipauto::report::SkipReason::Nested))
+            .map(|item| item.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            nested,
+            vec!["crate::orders::list_orders", "crate::users::handlers::list_users", "crate::users::handlers::get_user"]
+        );
+    }
+}
@@ -0,0 +1,12 @@
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+/// Discovers its own handlers
+#[utoipauto(paths = { path = "./nested_docs/src/orders", crate = "crate::orders" })]
+#[utoipauto::nest(path = "/api/orders")]
+#[derive(OpenApi)]
+#[openapi]
+pub struct OrdersApi;
+
+#[utoipa::path(get, path = "/")]
+pub fn list_orders() {}
@@ -0,0 +1,5 @@
+#[utoipa::path(get, path = "/")]
+pub fn list_users() {}
+
+#[utoipa::path(get, path = "/{id}", params(("id" = u32, Path)))]
+pub fn get_user() {}
@@ -0,0 +1,9 @@
+use utoipa::OpenApi;
+
+mod handlers;
+
+/// Nested under `/users`, the name of its module
+#[utoipauto::nest]
+#[derive(OpenApi)]
+#[openapi(paths(handlers::list_users, handlers::get_user))]
+pub struct UsersApi;
@@ -0,0 +1,51 @@
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+mod users {
+    #[utoipa::path(get, path = "/users")]
+    pub fn list() {}
+
+    #[utoipa::path(get, path = "/users/{id}", operation_id = "get_user")]
+    pub fn get() {}
+}
+
+mod orders {
+    #[utoipa::path(get, path = "/orders")]
+    pub fn list() {}
+}
+
+#[utoipauto(paths = "./operation_ids/src", operation_id = "module_path")]
+#[derive(OpenApi)]
+#[openapi(info(title = "Operation Ids Test Api"))]
+pub(crate) struct ApiDoc;
+
+#[utoipa::path(get, path = "/health")]
+pub fn health() {}
+
+fn main() {
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    fn operation_id(path: &str) -> Option<String> {
+        let openapi = ApiDoc::openapi();
+        openapi.paths.paths[path].get.as_ref().unwrap().operation_id.clone()
+    }
+
+    #[test]
+    fn test_module_operation_ids() {
+        assert_eq!(operation_id("/users").as_deref(), Some("users_list"));
+        assert_eq!(operation_id("/orders").as_deref(), Some("orders_list"));
+        assert_eq!(operation_id("/health").as_deref(), Some("health"));
+    }
+
+    #[test]
+    fn test_explicit_operation_id() {
+        assert_eq!(operation_id("/users/{id}").as_deref(), Some("get_user"));
+    }
+}
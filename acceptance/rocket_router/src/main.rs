@@ -0,0 +1,47 @@
+mod routes;
+mod v1;
+
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+#[utoipauto(paths = "./rocket_router/src", router = "rocket", prefixes = { "crate::v1" = "/api/v1" })]
+#[derive(OpenApi)]
+#[openapi(info(title = "Rocket Router Test Api"))]
+pub(crate) struct ApiDoc;
+
+fn main() {
+    let _rocket = ApiDoc::mount(rocket::build());
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    #[test]
+    fn test_routes() {
+        let uris: Vec<String> = ApiDoc::routes().iter().map(|route| route.uri.to_string()).collect();
+        assert_eq!(uris, vec!["/pets", "/pets", "/pets/<id>"]);
+    }
+
+    #[test]
+    fn test_mount() {
+        let client = Client::untracked(ApiDoc::mount(rocket::build())).unwrap();
+        assert_eq!(client.get("/pets").dispatch().status(), Status::Ok);
+        assert_eq!(client.post("/pets").dispatch().status(), Status::Ok);
+        assert_eq!(client.get("/api/v1/pets/1").dispatch().into_string().unwrap(), "pet 1");
+        assert_eq!(client.get("/pets/1").dispatch().status(), Status::NotFound);
+        assert_eq!(client.get("/internal").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn test_open_api() {
+        let api = ApiDoc::openapi();
+        let paths: Vec<&String> = api.paths.paths.keys().collect();
+        assert_eq!(paths, vec!["/pets", "/api/v1/pets/{id}"]);
+    }
+}
@@ -0,0 +1,5 @@
+#[utoipa::path(get, path = "/pets/{id}", params(("id" = u64, Path)), responses((status = 200, description = "The pet")))]
+#[rocket::get("/pets/<id>")]
+pub fn get_pet(id: u64) -> String {
+    format!("pet {id}")
+}
@@ -0,0 +1,88 @@
+use utoipa::OpenApi;
+use utoipauto::utoipauto;
+
+mod users {
+    use utoipa::{ToResponse, ToSchema};
+
+    #[derive(ToSchema)]
+    pub struct User {
+        pub name: String,
+        pub address: Address,
+    }
+
+    #[derive(ToSchema)]
+    pub struct Address {
+        pub city: String,
+    }
+
+    #[derive(ToSchema)]
+    pub enum Filter {
+        Country(Country),
+    }
+
+    #[derive(ToSchema)]
+    pub struct Country {
+        pub code: String,
+    }
+
+    #[derive(ToSchema)]
+    pub struct ErrorBody {
+        pub message: String,
+    }
+
+    #[derive(ToResponse)]
+    pub struct NotFound {
+        pub error: ErrorBody,
+    }
+
+    /// Not referenced by any handler
+    #[derive(ToSchema)]
+    pub struct InternalDto {
+        pub secret: String,
+    }
+
+    /// Not referenced by any handler
+    #[derive(ToResponse)]
+    pub struct Unused {
+        pub reason: String,
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/users",
+        params(("filter" = Filter, Query)),
+        responses((status = 200, body = Vec<User>), (status = 404, response = NotFound))
+    )]
+    pub fn list_users() {}
+}
+
+#[utoipauto(paths = "./schema_selection/src", schemas = "referenced", responses = "referenced")]
+#[derive(OpenApi)]
+#[openapi(info(title = "Schema Selection Test Api"))]
+pub(crate) struct ApiDoc;
+
+fn main() {
+    println!("Our OpenApi documentation {}", ApiDoc::openapi().to_pretty_json().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::OpenApi;
+
+    use crate::ApiDoc;
+
+    #[test]
+    fn test_referenced_schemas() {
+        let components = ApiDoc::openapi().components.unwrap();
+        let schemas = components.schemas.keys().map(String::as_str).collect::<Vec<_>>();
+        // `User` and `Address` are collected by utoipa from the response body, the others are registered
+        assert_eq!(schemas, vec!["Address", "Country", "ErrorBody", "Filter", "User"]);
+    }
+
+    #[test]
+    fn test_referenced_responses() {
+        let components = ApiDoc::openapi().components.unwrap();
+        let responses = components.responses.keys().map(String::as_str).collect::<Vec<_>>();
+        assert_eq!(responses, vec!["NotFound"]);
+    }
+}
@@ -0,0 +1,301 @@
+use proc_macro2::{Delimiter, Spacing, Span, TokenStream, TokenTree};
+use quote::quote;
+use syn::{punctuated::Punctuated, Item, ItemStruct, Meta};
+use utoipauto_core::attribute_utils::{build_new_openapi_attributes, openapi_tags, update_openapi_macro_attributes};
+use utoipauto_core::discover::{cfg_groups, to_token_streams, DiscoveredItem, DiscoveredKind, Discoverer};
+use utoipauto_core::file_utils::{extract_module_name_from_path, read_files};
+use utoipauto_core::rules::MarkerRule;
+use utoipauto_core::string_utils::{closest_match, discover};
+use utoipauto_core::tags::tag_definitions;
+use utoipauto_core::token_utils::Parameters;
+
+use crate::output::{render, Format, Record, Value};
+use crate::{Args, Command};
+
+/// Run the command and return what to print
+pub fn run(args: &Args) -> syn::Result<String> {
+    match &args.command {
+        Command::List => {
+            let records = discoverer(args)?
+                .filter(|item| item.kind == DiscoveredKind::Path)
+                .discover()?
+                .iter()
+                .map(handler_record)
+                .collect::<Vec<_>>();
+            Ok(render(&records, args.format))
+        }
+        Command::Schemas => {
+            let records = discoverer(args)?
+                .filter(|item| item.kind != DiscoveredKind::Path)
+                .discover()?
+                .iter()
+                .map(schema_record)
+                .collect::<Vec<_>>();
+            Ok(render(&records, args.format))
+        }
+        Command::Expand(doc) => expand(args, doc),
+    }
+}
+
+/// A discoverer configured from the command line options
+fn discoverer(args: &Args) -> syn::Result<Discoverer> {
+    let mut discoverer = Discoverer::new();
+    for (path, crate_name) in &args.paths {
+        discoverer = discoverer.crate_root(path, crate_name);
+    }
+    if let Some(name) = &args.fn_attribute_name {
+        discoverer = discoverer.function_attribute_name(name);
+    }
+    if let Some(name) = &args.schema_attribute_name {
+        discoverer = discoverer.schema_attribute_name(name);
+    }
+    if let Some(name) = &args.response_attribute_name {
+        discoverer = discoverer.response_attribute_name(name);
+    }
+    if let Some(path) = &args.rules {
+        let rules = MarkerRule::read_config(path).map_err(|e| error(format!("failed to read `{}`: {}", path, e)))?;
+        discoverer = discoverer.rules(rules);
+    }
+    Ok(discoverer)
+}
+
+/// `get  /users/{id}  crate::api::users  get_user  ./src/api/users.rs:12`
+fn handler_record(item: &DiscoveredItem) -> Record {
+    let route = item.route.clone().unwrap_or_default();
+    let segments = item.path_string();
+    let (module, name) = segments.rsplit_once("::").unwrap_or(("", &segments));
+    vec![
+        ("method", Value::List(route.methods)),
+        ("route", route.path.into()),
+        ("module", Value::String(module.to_string())),
+        ("handler", Value::String(name.to_string())),
+        ("location", Value::String(item.location())),
+        ("cfg", item.cfg.as_ref().map(ToString::to_string).into()),
+        ("skipped", item.skip_reason.as_ref().map(ToString::to_string).into()),
+    ]
+}
+
+/// `schema  crate::models::User  ./src/models.rs:8`
+fn schema_record(item: &DiscoveredItem) -> Record {
+    vec![
+        ("kind", Value::String(item.kind.to_string())),
+        ("item", Value::String(item.path_string())),
+        ("location", Value::String(item.location())),
+        ("cfg", item.cfg.as_ref().map(ToString::to_string).into()),
+        ("skipped", item.skip_reason.as_ref().map(ToString::to_string).into()),
+    ]
+}
+
+/// The `#[openapi(...)]` attributes `#[utoipauto]` generates for the documentation struct `doc`, then the ones of
+/// the hidden documentation structs listing the items of each cfg predicate
+fn expand(args: &Args, doc: &str) -> syn::Result<String> {
+    let mut docs = vec![];
+    for (path, crate_name) in &args.paths {
+        for file in read_files(path).map_err(|e| error(format!("failed to read `{}`: {}", path, e)))? {
+            let module = extract_module_name_from_path(&file.path, crate_name);
+            let module = quote!(#module).to_string().replace(' ', "");
+            find_docs(&module, file.file.items, &mut docs);
+        }
+    }
+    let names = docs.iter().map(|(path, _)| path.as_str()).collect::<Vec<_>>();
+    let Some((_, openapi_macro)) = docs.iter().find(|(path, item)| path == doc || item.ident == doc) else {
+        let suggestion = match closest_match(doc, &names) {
+            Some(suggestion) => format!(", did you mean `{}`?", suggestion),
+            None if names.is_empty() => ", no struct uses #[utoipauto]".to_string(),
+            None => format!(", found: {}", names.join(", ")),
+        };
+        return Err(error(format!("documentation struct `{}` not found{}", doc, suggestion)));
+    };
+
+    let attr = openapi_macro
+        .attrs
+        .iter()
+        .find(|attr| is_utoipauto(attr))
+        .expect("only the structs with #[utoipauto] are kept");
+    let params = match &attr.meta {
+        Meta::List(list) => list.parse_args::<Parameters>()?,
+        _ => Parameters::default(),
+    };
+    let discovery = discover(&params)?;
+
+    let mut records = vec![];
+    let mut defined_tags = vec![];
+    for (cfg, items) in cfg_groups(&discovery.items) {
+        let uto_tags = match &params.tags {
+            Some(config) => tag_definitions(config, &discovery.modules, &items, &defined_tags),
+            None => TokenStream::new(),
+        };
+        let (uto_paths, uto_models, uto_responses) = to_token_streams(items);
+        let attrs = match cfg {
+            None => {
+                let mut attrs = openapi_macro.attrs.clone();
+                update_openapi_macro_attributes(&mut attrs, &uto_paths, &uto_models, &uto_responses, &uto_tags)?;
+                defined_tags = openapi_tags(&attrs)?;
+                attrs
+            }
+            Some(_) => vec![build_new_openapi_attributes(
+                Punctuated::new(),
+                &uto_paths,
+                &uto_models,
+                &uto_responses,
+                &uto_tags,
+            )?],
+        };
+        let attribute = attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("openapi"))
+            .map(|attr| compact(quote!(#attr)))
+            .unwrap_or_default();
+        records.push(vec![
+            ("cfg", cfg.as_ref().map(ToString::to_string).into()),
+            ("attribute", Value::String(attribute)),
+        ]);
+    }
+
+    Ok(match args.format {
+        Format::Json => render(&records, Format::Json),
+        // The attributes are too long for a table, they are printed as they would be written
+        Format::Table => records
+            .iter()
+            .map(|record| match &record[0].1 {
+                Value::String(cfg) => format!("#[cfg({})]\n{}", cfg, cell_string(&record[1].1)),
+                _ => cell_string(&record[1].1),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    })
+}
+
+/// The tokens as they would be written by hand: `#[openapi(paths(crate::api::get_user))]`
+fn compact(tokens: TokenStream) -> String {
+    let mut output = String::new();
+    // Words are separated by a space: `dyn Trait`
+    let mut after_word = false;
+    for tree in tokens {
+        match tree {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                let inner = compact(group.stream());
+                output.push_str(open);
+                output.push_str(inner.trim_end_matches([',', ' ']));
+                output.push_str(close);
+                after_word = false;
+            }
+            TokenTree::Punct(punct) => {
+                match punct.as_char() {
+                    ',' => output.push_str(", "),
+                    '=' if punct.spacing() == Spacing::Alone => output.push_str(" = "),
+                    c => output.push(c),
+                }
+                after_word = false;
+            }
+            word => {
+                if after_word {
+                    output.push(' ');
+                }
+                output.push_str(&word.to_string());
+                after_word = true;
+            }
+        }
+    }
+    output
+}
+
+fn cell_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Collect the structs with the `#[utoipauto]` attribute, with their rust path, from the items and the inline modules
+fn find_docs(module: &str, items: Vec<Item>, docs: &mut Vec<(String, ItemStruct)>) {
+    for item in items {
+        match item {
+            Item::Struct(item) if item.attrs.iter().any(is_utoipauto) => {
+                docs.push((format!("{}::{}", module, item.ident), item));
+            }
+            Item::Mod(item) => {
+                if let Some((_, items)) = item.content {
+                    find_docs(&format!("{}::{}", module, item.ident), items, docs);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_utoipauto(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "utoipauto")
+}
+
+fn error(message: String) -> syn::Error {
+    syn::Error::new(Span::call_site(), message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+    use crate::output::Format;
+    use crate::{Args, Command};
+
+    fn args(command: Command, path: &str) -> Args {
+        Args {
+            command,
+            format: Format::Json,
+            paths: vec![(path.to_string(), "crate".to_string())],
+            fn_attribute_name: None,
+            schema_attribute_name: None,
+            response_attribute_name: None,
+            rules: None,
+        }
+    }
+
+    #[test]
+    fn test_list() {
+        let output = run(&args(
+            Command::List,
+            "../utoipauto/tests/default_features/controllers/controller1.rs",
+        ))
+        .unwrap();
+        assert!(output.contains("\"handler\": \"route1\""));
+        assert!(output.contains("\"handler\": \"route_ignored\""));
+        assert!(output.contains("\"location\": \"../utoipauto/tests/default_features/controllers/controller1.rs:5\""));
+    }
+
+    #[test]
+    fn test_schemas() {
+        let output = run(&args(Command::Schemas, "../utoipauto/tests/default_features/models.rs")).unwrap();
+        assert!(output.starts_with("[\n  {\"kind\": \"schema\""));
+    }
+
+    #[test]
+    fn test_compact() {
+        let tokens = quote::quote!(#[openapi(paths(crate::api::get_user,), components(schemas(Page<User>)), info(title = "API"))]);
+        assert_eq!(
+            super::compact(tokens),
+            "#[openapi(paths(crate::api::get_user), components(schemas(Page<User>)), info(title = \"API\"))]"
+        );
+    }
+
+    #[test]
+    fn test_expand_not_found() {
+        let error = run(&args(
+            Command::Expand("ApiDocs".to_string()),
+            "../utoipauto/tests/default_features/controllers/controller1.rs",
+        ))
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "documentation struct `ApiDocs` not found, no struct uses #[utoipauto]"
+        );
+    }
+}
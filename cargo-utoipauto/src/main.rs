@@ -0,0 +1,186 @@
+//! `cargo utoipauto`: list and inspect the items discovered by `#[utoipauto]`, without `cargo expand`
+//!
+//! Run it from the workspace root, like the paths given to the macro.
+
+mod commands;
+mod output;
+
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use output::Format;
+
+const USAGE: &str = "\
+List and inspect the items discovered by utoipauto
+
+Usage: cargo utoipauto <COMMAND> [OPTIONS]
+
+Commands:
+  list           Print the discovered handlers with their method, route, module and location
+  schemas        Print the discovered schemas and responses
+  expand <DOC>   Print the #[openapi(...)] attribute generated for the documentation struct DOC
+
+Options:
+  --path <PATH>                       A file or folder to explore, ./src by default, can be repeated
+  --crate <NAME>                      The crate of the previous --path, crate by default
+  --function-attribute-name <NAME>    The attribute of the functions to document, utoipa by default
+  --schema-attribute-name <NAME>      The derive or trait of the schemas to document, ToSchema by default
+  --response-attribute-name <NAME>    The derive or trait of the responses to document, ToResponse by default
+  --rules <FILE>                      A file of extra discovery rules
+  --format <table|json>               The output format, table by default
+  -h, --help                          Print this help
+
+list and schemas include the skipped items. expand reads the options of the #[utoipauto(...)] attribute of DOC,
+and only uses --path to find the struct.";
+
+/// The subcommand to run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    List,
+    Schemas,
+    /// The name or the rust path of the documentation struct
+    Expand(String),
+}
+
+/// The parsed command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Args {
+    pub command: Command,
+    pub format: Format,
+    /// The files and folders to explore, with the name of the crate they belong to
+    pub paths: Vec<(String, String)>,
+    pub fn_attribute_name: Option<String>,
+    pub schema_attribute_name: Option<String>,
+    pub response_attribute_name: Option<String>,
+    pub rules: Option<String>,
+}
+
+impl Args {
+    /// Parse the arguments, without the binary name
+    ///
+    /// The leading `utoipauto` given by `cargo utoipauto` is skipped
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut args = args.into_iter().peekable();
+        if args.peek().map(String::as_str) == Some("utoipauto") {
+            args.next();
+        }
+
+        let mut command = None;
+        let mut format = Format::Table;
+        let mut paths: Vec<(String, String)> = vec![];
+        let mut fn_attribute_name = None;
+        let mut schema_attribute_name = None;
+        let mut response_attribute_name = None;
+        let mut rules = None;
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("`{}` requires a value", name));
+            match arg.as_str() {
+                "--path" => paths.push((value(&arg)?, "crate".to_string())),
+                "--crate" => {
+                    let name = value(&arg)?;
+                    match paths.last_mut() {
+                        Some((_, crate_name)) => *crate_name = name,
+                        None => return Err("`--crate` must follow a `--path`".to_string()),
+                    }
+                }
+                "--function-attribute-name" => fn_attribute_name = Some(value(&arg)?),
+                "--schema-attribute-name" => schema_attribute_name = Some(value(&arg)?),
+                "--response-attribute-name" => response_attribute_name = Some(value(&arg)?),
+                "--rules" => rules = Some(value(&arg)?),
+                "--format" => {
+                    format = match value(&arg)?.as_str() {
+                        "table" => Format::Table,
+                        "json" => Format::Json,
+                        other => return Err(format!("unknown format `{}`, expected `table` or `json`", other)),
+                    }
+                }
+                "list" | "schemas" | "expand" if command.is_none() => {
+                    command = Some(match arg.as_str() {
+                        "list" => Command::List,
+                        "schemas" => Command::Schemas,
+                        _ => Command::Expand(value("expand")?),
+                    })
+                }
+                other if other.starts_with('-') => return Err(format!("unknown option `{}`", other)),
+                other => return Err(format!("unexpected argument `{}`", other)),
+            }
+        }
+
+        if paths.is_empty() {
+            paths.push(("./src".to_string(), "crate".to_string()));
+        }
+        Ok(Args {
+            command: command.ok_or("a command is required: `list`, `schemas` or `expand <DOC>`")?,
+            format,
+            paths,
+            fn_attribute_name,
+            schema_attribute_name,
+            response_attribute_name,
+            rules,
+        })
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let result = Args::parse(args).and_then(|args| commands::run(&args).map_err(|e| e.to_string()));
+    match result {
+        Ok(output) => {
+            // The output may be piped to a command that stops reading early, like `head`
+            let _ = writeln!(io::stdout(), "{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE.lines().nth(2).unwrap_or_default());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Args, Command};
+    use crate::output::Format;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&[
+            "utoipauto",
+            "list",
+            "--path",
+            "./api/src",
+            "--crate",
+            "api",
+            "--format",
+            "json",
+        ])
+        .unwrap();
+        assert_eq!(args.command, Command::List);
+        assert_eq!(args.format, Format::Json);
+        assert_eq!(args.paths, vec![("./api/src".to_string(), "api".to_string())]);
+
+        let args = parse(&["expand", "ApiDoc"]).unwrap();
+        assert_eq!(args.command, Command::Expand("ApiDoc".to_string()));
+        assert_eq!(args.format, Format::Table);
+        assert_eq!(args.paths, vec![("./src".to_string(), "crate".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_invalid_args() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["expand"]).is_err());
+        assert!(parse(&["list", "--crate", "api"]).is_err());
+        assert_eq!(
+            parse(&["list", "--format", "yaml"]).unwrap_err(),
+            "unknown format `yaml`, expected `table` or `json`"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt::Write as _;
+
+/// How the records are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned columns, for humans
+    Table,
+    /// An array of objects, for scripts
+    Json,
+}
+
+/// A field of a record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Null,
+    String(String),
+    List(Vec<String>),
+}
+
+/// One line of the output: the fields, in the order of the columns
+pub type Record = Vec<(&'static str, Value)>;
+
+impl From<Option<String>> for Value {
+    fn from(value: Option<String>) -> Self {
+        value.map_or(Value::Null, Value::String)
+    }
+}
+
+/// Print the records in the given format
+pub fn render(records: &[Record], format: Format) -> String {
+    match format {
+        Format::Table => render_table(records),
+        Format::Json => render_json(records),
+    }
+}
+
+/// The records as aligned columns, with the field names upper cased as headers
+fn render_table(records: &[Record]) -> String {
+    let Some(first) = records.first() else {
+        return "nothing found".to_string();
+    };
+    let mut rows = vec![first
+        .iter()
+        .map(|(name, _)| name.to_uppercase().replace('_', " "))
+        .collect::<Vec<_>>()];
+    rows.extend(
+        records
+            .iter()
+            .map(|record| record.iter().map(|(_, value)| cell(value)).collect()),
+    );
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|column| rows.iter().map(|row| row[column].chars().count()).max().unwrap_or(0))
+        .collect();
+
+    let mut table = String::new();
+    for row in &rows {
+        let mut line = String::new();
+        for (value, width) in row.iter().zip(&widths) {
+            let _ = write!(line, "{:<width$}  ", value, width = width);
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table.trim_end().to_string()
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(string) => string.clone(),
+        Value::List(list) => list.join(","),
+    }
+}
+
+/// The records as a JSON array of objects
+fn render_json(records: &[Record]) -> String {
+    let objects = records
+        .iter()
+        .map(|record| {
+            let fields = record
+                .iter()
+                .map(|(name, value)| format!("\"{}\": {}", name, json(value)))
+                .collect::<Vec<_>>();
+            format!("  {{{}}}", fields.join(", "))
+        })
+        .collect::<Vec<_>>();
+    match objects.is_empty() {
+        true => "[]".to_string(),
+        false => format!("[\n{}\n]", objects.join(",\n")),
+    }
+}
+
+fn json(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::String(string) => json_string(string),
+        Value::List(list) => format!(
+            "[{}]",
+            list.iter().map(|s| json_string(s)).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, Format, Record, Value};
+
+    fn records() -> Vec<Record> {
+        vec![
+            vec![
+                ("method", Value::List(vec!["get".to_string()])),
+                ("route", Value::String("/users".to_string())),
+                ("handler", Value::String("list".to_string())),
+                ("skipped", Value::Null),
+            ],
+            vec![
+                ("method", Value::List(vec!["get".to_string(), "head".to_string()])),
+                ("route", Value::String("/health".to_string())),
+                ("handler", Value::String("health".to_string())),
+                ("skipped", Value::String("it is \"ignored\"".to_string())),
+            ],
+        ]
+    }
+
+    #[test]
+    fn test_render_table() {
+        assert_eq!(
+            render(&records(), Format::Table),
+            "METHOD    ROUTE    HANDLER  SKIPPED\n\
+             get       /users   list     -\n\
+             get,head  /health  health   it is \"ignored\""
+        );
+        assert_eq!(render(&[], Format::Table), "nothing found");
+    }
+
+    #[test]
+    fn test_render_json() {
+        assert_eq!(
+            render(&records(), Format::Json),
+            "[\n  \
+             {\"method\": [\"get\"], \"route\": \"/users\", \"handler\": \"list\", \"skipped\": null},\n  \
+             {\"method\": [\"get\", \"head\"], \"route\": \"/health\", \"handler\": \"health\", \"skipped\": \"it is \\\"ignored\\\"\"}\n\
+             ]"
+        );
+        assert_eq!(render(&[], Format::Json), "[]");
+    }
+}
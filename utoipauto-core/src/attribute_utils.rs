@@ -1,12 +1,13 @@
-use proc_macro2::TokenStream;
-use syn::{punctuated::Punctuated, Attribute, Meta, Token};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use syn::{parse::Parser, punctuated::Punctuated, Attribute, LitStr, Meta, Token};
 
 pub fn update_openapi_macro_attributes(
     macro_attibutes: &mut Vec<Attribute>,
     uto_paths: &TokenStream,
     uto_models: &TokenStream,
     uto_responses: &TokenStream,
-) {
+    uto_tags: &TokenStream,
+) -> syn::Result<()> {
     let mut is_ok = false;
     for attr in macro_attibutes {
         if !attr.path().is_ident("openapi") {
@@ -16,35 +17,58 @@ pub fn update_openapi_macro_attributes(
         match &attr.meta {
             // #[openapi]
             Meta::Path(_path) => {
-                *attr = build_new_openapi_attributes(Punctuated::new(), uto_paths, uto_models, uto_responses);
+                *attr =
+                    build_new_openapi_attributes(Punctuated::new(), uto_paths, uto_models, uto_responses, uto_tags)?;
             }
             // #[openapi()] or #[openapi(attribute(...))]
             Meta::List(meta_list) => {
                 let nested = meta_list
                     .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
-                    .expect("Expected a list of attributes inside #[openapi(...)]!");
-                *attr = build_new_openapi_attributes(nested, uto_paths, uto_models, uto_responses);
+                    .map_err(|e| {
+                        syn::Error::new(
+                            e.span(),
+                            format!("Expected a list of attributes inside #[openapi(...)]: {e}"),
+                        )
+                    })?;
+                *attr = build_new_openapi_attributes(nested, uto_paths, uto_models, uto_responses, uto_tags)?;
             }
             // This would be #[openapi = "foo"], which is not valid
-            Meta::NameValue(_) => panic!("Expected #[openapi(...)], but found #[openapi = value]!"),
+            Meta::NameValue(_) => {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "Expected #[openapi(...)], but found #[openapi = value]!",
+                ))
+            }
         }
     }
     if !is_ok {
-        panic!("No utoipa::openapi Macro found !");
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "No utoipa::openapi Macro found ! Please put utoipauto before #[derive] and #[openapi]",
+        ));
     }
+    Ok(())
 }
 
 /// Build the new openapi macro attribute with the newly discovered paths
+///
+/// `uto_tags` are `(name = "...", description = "...")` tag definitions, added after the ones of `tags(...)`
+/// that have another name
 pub fn build_new_openapi_attributes(
     nested_attributes: Punctuated<Meta, Token![,]>,
     uto_paths: &TokenStream,
     uto_models: &TokenStream,
     uto_responses: &TokenStream,
-) -> Attribute {
+    uto_tags: &TokenStream,
+) -> syn::Result<Attribute> {
     let paths = extract_paths(&nested_attributes);
-    let schemas = extract_components(&nested_attributes, "schemas");
-    let responses = extract_components(&nested_attributes, "responses");
-    let remaining_nested_attributes = remove_paths_and_components(nested_attributes);
+    let schemas = extract_components(&nested_attributes, "schemas")?;
+    let responses = extract_components(&nested_attributes, "responses")?;
+    let tags = match uto_tags.is_empty() {
+        true => None,
+        false => Some(merge_tags(&nested_attributes, uto_tags)?),
+    };
+    let remaining_nested_attributes = remove_paths_and_components(nested_attributes, tags.is_some());
 
     let uto_paths = match uto_paths.is_empty() {
         true => TokenStream::new(),
@@ -58,18 +82,151 @@ pub fn build_new_openapi_attributes(
         true => TokenStream::new(),
         false => quote::quote!(#uto_responses,),
     };
+    let tags = tags.map(|tags| quote::quote!(tags(#tags),));
     let uto_macro = quote::quote!(
         paths(#uto_paths #paths),components(schemas(#uto_models #schemas),responses(#uto_responses #responses)),
-        #remaining_nested_attributes
+        #tags #remaining_nested_attributes
     );
 
-    syn::parse_quote! { #[openapi( #uto_macro )] }
+    Ok(syn::parse_quote! { #[openapi( #uto_macro )] })
+}
+
+/// Append `modifiers` to the `modifiers(...)` of the openapi macro, after the ones already listed
+pub fn add_openapi_modifiers(macro_attributes: &mut [Attribute], modifiers: &TokenStream) -> syn::Result<()> {
+    add_to_list(macro_attributes, "modifiers", modifiers, false)
+}
+
+/// Prepend `modifiers` to the `modifiers(...)` of the openapi macro, so that they run before the ones already listed
+pub fn prepend_openapi_modifiers(macro_attributes: &mut [Attribute], modifiers: &TokenStream) -> syn::Result<()> {
+    add_to_list(macro_attributes, "modifiers", modifiers, true)
+}
+
+/// Append `nests` to the `nest(...)` of the openapi macro, after the ones already listed
+pub fn add_openapi_nests(macro_attributes: &mut [Attribute], nests: &TokenStream) -> syn::Result<()> {
+    add_to_list(macro_attributes, "nest", nests, false)
+}
+
+/// Add `entries` to the `name(...)` list of the openapi macro, before the ones already listed if `first`
+fn add_to_list(macro_attributes: &mut [Attribute], name: &str, entries: &TokenStream, first: bool) -> syn::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let ident = syn::Ident::new(name, Span::call_site());
+    for attr in macro_attributes
+        .iter_mut()
+        .filter(|attr| attr.path().is_ident("openapi"))
+    {
+        let mut nested = match &attr.meta {
+            Meta::List(meta_list) => meta_list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?,
+            _ => Punctuated::new(),
+        };
+        let existing = nested.iter_mut().find_map(|meta| match meta {
+            Meta::List(list) if list.path.is_ident(name) => Some(list),
+            _ => None,
+        });
+        match existing {
+            Some(list) => {
+                let tokens = &list.tokens;
+                list.tokens = match (tokens.is_empty(), first) {
+                    (true, _) => entries.clone(),
+                    (false, true) => quote::quote!(#entries, #tokens),
+                    (false, false) => quote::quote!(#tokens, #entries),
+                };
+            }
+            None => nested.push(syn::parse_quote!(#ident(#entries))),
+        }
+        *attr = syn::parse_quote! { #[openapi(#nested)] };
+    }
+    Ok(())
+}
+
+/// The names of the types already listed in the `modifiers(&SecurityAddon, ...)` of the openapi macro
+pub fn openapi_modifiers(macro_attributes: &[Attribute]) -> syn::Result<Vec<String>> {
+    Ok(list_entries(macro_attributes, "modifiers")?
+        .iter()
+        .filter_map(|modifier| match modifier {
+            syn::Expr::Reference(reference) => last_ident(&reference.expr),
+            _ => None,
+        })
+        .collect())
+}
+
+/// The names of the structs already listed in the `nest((path = "/users", api = UsersApi), ...)` of the openapi
+/// macro
+pub fn openapi_nests(macro_attributes: &[Attribute]) -> syn::Result<Vec<String>> {
+    Ok(list_entries(macro_attributes, "nest")?
+        .iter()
+        .filter_map(|nest| match nest {
+            syn::Expr::Tuple(tuple) => tuple.elems.iter().find_map(|elem| match elem {
+                syn::Expr::Assign(assign) if last_ident(&assign.left).is_some_and(|name| name == "api") => {
+                    last_ident(&assign.right)
+                }
+                _ => None,
+            }),
+            _ => None,
+        })
+        .collect())
+}
+
+/// The names of the tags already defined in the `tags((name = "users", ...), ...)` of the openapi macro
+pub fn openapi_tags(macro_attributes: &[Attribute]) -> syn::Result<Vec<String>> {
+    let name = |elem: &syn::Expr| match elem {
+        syn::Expr::Assign(assign) if last_ident(&assign.left).is_some_and(|key| key == "name") => {
+            match &*assign.right {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(name),
+                    ..
+                }) => Some(name.value()),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    Ok(list_entries(macro_attributes, "tags")?
+        .iter()
+        .filter_map(|tag| match tag {
+            syn::Expr::Tuple(tuple) => tuple.elems.iter().find_map(name),
+            // `(name = "users")`
+            syn::Expr::Paren(paren) => name(&paren.expr),
+            _ => None,
+        })
+        .collect())
+}
+
+/// The entries of the `<name>(...)` lists of the openapi macro
+fn list_entries(macro_attributes: &[Attribute], name: &str) -> syn::Result<Vec<syn::Expr>> {
+    let mut entries = Vec::new();
+    for attr in macro_attributes.iter().filter(|attr| attr.path().is_ident("openapi")) {
+        let Meta::List(meta_list) = &attr.meta else {
+            continue;
+        };
+        let nested = meta_list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        for meta in nested {
+            match meta {
+                Meta::List(list) if list.path.is_ident(name) => {
+                    entries.extend(list.parse_args_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated)?)
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// `UsersApi` for `crate::users::UsersApi`
+fn last_ident(expr: &syn::Expr) -> Option<String> {
+    match expr {
+        syn::Expr::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
 }
 
-fn remove_paths_and_components(nested_attributes: Punctuated<Meta, Token![,]>) -> TokenStream {
+/// The attributes other than the paths and the components, and the tags if they are rebuilt
+fn remove_paths_and_components(nested_attributes: Punctuated<Meta, Token![,]>, remove_tags: bool) -> TokenStream {
     let mut remaining = Vec::new();
     for meta in nested_attributes {
         match meta {
+            Meta::List(list) if remove_tags && list.path.is_ident("tags") => (),
             Meta::List(list) if list.path.is_ident("paths") => (),
             Meta::List(list) if list.path.is_ident("components") => (),
             // These should be handled by removing `components`, this is just in case they occur outside of `components` for some reason.
@@ -91,25 +248,69 @@ fn extract_paths(nested_attributes: &Punctuated<Meta, Token![,]>) -> TokenStream
         .unwrap_or_else(TokenStream::new)
 }
 
-fn extract_components(nested_attributes: &Punctuated<Meta, Token![,]>, component_kind: &str) -> TokenStream {
-    nested_attributes
-        .iter()
-        .find_map(|meta| {
-            let Meta::List(list) = meta else { return None };
-            if !list.path.is_ident("components") {
-                return None;
-            }
+/// The tags of `tags(...)`, then the discovered ones whose name isn't already defined
+fn merge_tags(nested_attributes: &Punctuated<Meta, Token![,]>, uto_tags: &TokenStream) -> syn::Result<TokenStream> {
+    let parse = Punctuated::<TokenTree, Token![,]>::parse_terminated;
+    let mut tags = Vec::new();
+    for meta in nested_attributes {
+        let Meta::List(list) = meta else { continue };
+        if list.path.is_ident("tags") {
+            tags.extend(list.parse_args_with(parse)?);
+        }
+    }
+    for tag in parse.parse2(uto_tags.clone())? {
+        let name = tag_name(&tag);
+        if name.is_none() || !tags.iter().any(|existing| tag_name(existing) == name) {
+            tags.push(tag);
+        }
+    }
+    Ok(quote::quote!(#(#tags),*))
+}
 
-            let nested = list
-                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
-                .expect("Expected a list of attributes inside components(...)!");
+/// The `name = "..."` of a `(name = "...", description = "...")` tag definition
+fn tag_name(tag: &TokenTree) -> Option<String> {
+    let TokenTree::Group(group) = tag else { return None };
+    let tokens = group.stream().into_iter().collect::<Vec<_>>();
+    tokens.windows(3).find_map(|window| match window {
+        [TokenTree::Ident(key), TokenTree::Punct(eq), TokenTree::Literal(name)]
+            if key == "name" && eq.as_char() == '=' =>
+        {
+            syn::parse_str::<LitStr>(&name.to_string())
+                .ok()
+                .map(|name| name.value())
+        }
+        _ => None,
+    })
+}
 
-            nested.iter().find_map(|meta| {
-                let Meta::List(list) = meta else { return None };
-                list.path.is_ident(component_kind).then(|| list.tokens.clone())
-            })
-        })
-        .unwrap_or_else(TokenStream::new)
+fn extract_components(
+    nested_attributes: &Punctuated<Meta, Token![,]>,
+    component_kind: &str,
+) -> syn::Result<TokenStream> {
+    for meta in nested_attributes {
+        let Meta::List(list) = meta else { continue };
+        if !list.path.is_ident("components") {
+            continue;
+        }
+
+        let nested = list
+            .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .map_err(|e| {
+                syn::Error::new(
+                    e.span(),
+                    format!("Expected a list of attributes inside components(...): {e}"),
+                )
+            })?;
+
+        let components = nested.iter().find_map(|meta| {
+            let Meta::List(list) = meta else { return None };
+            list.path.is_ident(component_kind).then(|| list.tokens.clone())
+        });
+        if let Some(components) = components {
+            return Ok(components);
+        }
+    }
+    Ok(TokenStream::new())
 }
 
 #[cfg(test)]
@@ -139,7 +340,9 @@ mod test {
                 &quote::quote!(crate::api::test),
                 &TokenStream::new(),
                 &TokenStream::new(),
+                &TokenStream::new(),
             )
+            .unwrap()
             .to_token_stream()
             .to_string()
             .replace(' ', ""),
@@ -155,7 +358,9 @@ mod test {
                 &quote::quote!(crate::api::test),
                 &TokenStream::new(),
                 &TokenStream::new(),
+                &TokenStream::new(),
             )
+            .unwrap()
             .to_token_stream()
             .to_string()
             .replace(' ', ""),
@@ -171,7 +376,9 @@ mod test {
                 &quote::quote!(crate::api::test),
                 &quote::quote!(model),
                 &TokenStream::new(),
+                &TokenStream::new(),
             )
+            .unwrap()
             .to_token_stream()
             .to_string()
             .replace(' ', ""),
@@ -187,7 +394,9 @@ mod test {
                 &quote::quote!(crate::api::test),
                 &quote::quote!(model),
                 &TokenStream::new(),
+                &TokenStream::new(),
             )
+            .unwrap()
             .to_token_stream()
             .to_string()
             .replace(' ', ""),
@@ -203,7 +412,9 @@ mod test {
                 &quote::quote!(crate::api::test),
                 &TokenStream::new(),
                 &quote::quote!(response),
+                &TokenStream::new(),
             )
+            .unwrap()
             .to_token_stream()
             .to_string()
             .replace(' ', ""),
@@ -219,7 +430,9 @@ mod test {
                 &quote::quote!(crate::api::test),
                 &quote::quote!(model),
                 &quote::quote!(response),
+                &TokenStream::new(),
             )
+            .unwrap()
             .to_token_stream()
             .to_string()
             .replace(' ', ""),
@@ -235,7 +448,9 @@ mod test {
                 &quote::quote!(crate::api::test),
                 &TokenStream::new(),
                 &quote::quote!(response),
+                &TokenStream::new(),
             )
+            .unwrap()
             .to_token_stream()
             .to_string()
             .replace(' ', ""),
@@ -251,7 +466,9 @@ mod test {
                 &quote::quote!(crate::api::test),
                 &quote::quote!(model),
                 &TokenStream::new(),
+                &TokenStream::new(),
             )
+            .unwrap()
             .to_token_stream()
             .to_string()
             .replace(' ', ""),
@@ -267,7 +484,9 @@ mod test {
             &quote::quote!(crate::api::test),
             &quote::quote!(model),
             &TokenStream::new(),
-        );
+            &TokenStream::new(),
+        )
+        .unwrap();
         assert_eq!(
             attrs[0].to_token_stream().to_string().replace(' ', ""),
             "#[openapi(paths(crate::api::test,),components(schemas(model,),responses()),)]".to_string()
@@ -282,10 +501,113 @@ mod test {
             &quote::quote!(crate::api::test),
             &quote::quote!(model),
             &TokenStream::new(),
-        );
+            &TokenStream::new(),
+        )
+        .unwrap();
         assert_eq!(
             attrs[0].to_token_stream().to_string().replace(' ', ""),
             "#[openapi(paths(crate::api::test,p1),components(schemas(model,m1),responses(r1)),)]".to_string()
         );
     }
+
+    #[test]
+    fn test_add_openapi_modifiers() {
+        let mut attrs = vec![syn::parse_quote!(#[openapi(info(title = "Api"), modifiers(&SecurityAddon))])];
+        super::add_openapi_modifiers(&mut attrs, &quote::quote!(&Registered)).unwrap();
+        assert_eq!(
+            attrs[0].to_token_stream().to_string().replace(' ', ""),
+            "#[openapi(info(title=\"Api\"),modifiers(&SecurityAddon,&Registered))]".to_string()
+        );
+
+        let mut attrs = vec![syn::parse_quote!(#[openapi(info(title = "Api"))])];
+        super::add_openapi_modifiers(&mut attrs, &quote::quote!(&Registered)).unwrap();
+        assert_eq!(
+            attrs[0].to_token_stream().to_string().replace(' ', ""),
+            "#[openapi(info(title=\"Api\"),modifiers(&Registered))]".to_string()
+        );
+
+        let mut attrs = vec![syn::parse_quote!(#[openapi(modifiers(&SecurityAddon))])];
+        super::prepend_openapi_modifiers(&mut attrs, &quote::quote!(&__utoipauto_cfg_ApiDoc)).unwrap();
+        assert_eq!(
+            attrs[0].to_token_stream().to_string().replace(' ', ""),
+            "#[openapi(modifiers(&__utoipauto_cfg_ApiDoc,&SecurityAddon))]".to_string()
+        );
+    }
+
+    #[test]
+    fn test_openapi_modifiers() {
+        let attrs =
+            vec![syn::parse_quote!(#[openapi(info(title = "Api"), modifiers(&SecurityAddon, &crate::Servers))])];
+        assert_eq!(
+            super::openapi_modifiers(&attrs).unwrap(),
+            vec!["SecurityAddon", "Servers"]
+        );
+    }
+
+    #[test]
+    fn test_openapi_tags() {
+        let attrs =
+            vec![syn::parse_quote!(#[openapi(tags((name = "users", description = "Users"), (name = "billing")))])];
+        assert_eq!(super::openapi_tags(&attrs).unwrap(), vec!["users", "billing"]);
+    }
+
+    #[test]
+    fn test_openapi_nests() {
+        let mut attrs = vec![
+            syn::parse_quote!(#[openapi(nest((path = "/users", api = crate::users::UsersApi, tags = ["users"])))]),
+        ];
+        assert_eq!(super::openapi_nests(&attrs).unwrap(), vec!["UsersApi"]);
+        super::add_openapi_nests(&mut attrs, &quote::quote!((path = "/orders", api = OrdersApi))).unwrap();
+        assert_eq!(
+            attrs[0].to_token_stream().to_string().replace(' ', ""),
+            "#[openapi(nest((path=\"/users\",api=crate::users::UsersApi,tags=[\"users\"]),(path=\"/orders\",api=OrdersApi)))]"
+        );
+    }
+
+    #[test]
+    fn test_build_new_openapi_attributes_tags() {
+        assert_eq!(
+            super::build_new_openapi_attributes(
+                syn::parse_quote!(tags((name = "users", description = "Handwritten")), info(title = "Api")),
+                &TokenStream::new(),
+                &TokenStream::new(),
+                &TokenStream::new(),
+                &quote::quote!((name = "billing", description = "Invoices"), (name = "users")),
+            )
+            .unwrap()
+            .to_token_stream()
+            .to_string()
+            .replace(' ', ""),
+            "#[openapi(paths(),components(schemas(),responses()),\
+             tags((name=\"users\",description=\"Handwritten\"),(name=\"billing\",description=\"Invoices\")),\
+             info(title=\"Api\"))]"
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_update_openapi_attributes_missing_openapi() {
+        let mut attrs = vec![syn::parse_quote!(#[derive(OpenApi)])];
+        let result = super::update_openapi_macro_attributes(
+            &mut attrs,
+            &quote::quote!(crate::api::test),
+            &TokenStream::new(),
+            &TokenStream::new(),
+            &TokenStream::new(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_update_openapi_attributes_name_value() {
+        let mut attrs = vec![syn::parse_quote!(#[openapi = "foo"])];
+        let result = super::update_openapi_macro_attributes(
+            &mut attrs,
+            &quote::quote!(crate::api::test),
+            &TokenStream::new(),
+            &TokenStream::new(),
+            &TokenStream::new(),
+        );
+        assert!(result.is_err());
+    }
 }
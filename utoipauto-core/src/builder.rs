@@ -0,0 +1,439 @@
+//! Discover the items to document from a build script instead of the proc macro
+//!
+//! The build script writes a registry file listing the discovered items to `OUT_DIR`:
+//!
+//! ```rust,ignore
+//! // build.rs
+//! fn main() {
+//!     utoipauto_core::builder::Builder::new()
+//!         .root("src")
+//!         .generate("openapi_registry.rs")
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! And `#[utoipauto(include = "openapi_registry.rs")]` adds them to the `#[openapi]` macro without exploring the files.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, parenthesized, Attribute, Expr, ExprLit, Ident, Lit, LitInt, LitStr, Meta, Token, Visibility};
+
+use crate::discover::{DiscoveredItem, DiscoveredKind, Discoverer, SkipReason};
+use crate::references::{Position, TypeReference};
+use crate::route_utils::Route;
+use crate::rules::DiscoveryRule;
+
+/// The header of the generated registry files
+const HEADER: &str = "// @generated by utoipauto, do not edit";
+
+/// Generate a registry file of the items to document, from a build script
+#[derive(Default)]
+pub struct Builder {
+    discoverer: Discoverer,
+    /// The explored files and folders, to rerun the build script when they change
+    roots: Vec<String>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Explore a file, or a folder recursively, of the current crate: `src`
+    pub fn root(self, path: impl Into<String>) -> Self {
+        self.crate_root(path, "crate")
+    }
+
+    /// Explore a file, or a folder recursively, whose items are reachable from `crate_name`
+    pub fn crate_root(mut self, path: impl Into<String>, crate_name: impl Into<String>) -> Self {
+        let path = path.into();
+        self.roots.push(path.clone());
+        self.discoverer = self.discoverer.crate_root(path, crate_name);
+        self
+    }
+
+    /// The attribute of the functions to document, `utoipa` by default
+    pub fn function_attribute_name(mut self, name: impl Into<String>) -> Self {
+        self.discoverer = self.discoverer.function_attribute_name(name);
+        self
+    }
+
+    /// The derive or trait of the schemas to document, `ToSchema` by default
+    pub fn schema_attribute_name(mut self, name: impl Into<String>) -> Self {
+        self.discoverer = self.discoverer.schema_attribute_name(name);
+        self
+    }
+
+    /// The derive or trait of the responses to document, `ToResponse` by default
+    pub fn response_attribute_name(mut self, name: impl Into<String>) -> Self {
+        self.discoverer = self.discoverer.response_attribute_name(name);
+        self
+    }
+
+    /// Classify the items with an extra discovery rule
+    pub fn rule(mut self, rule: impl DiscoveryRule + 'static) -> Self {
+        self.discoverer = self.discoverer.rule(rule);
+        self
+    }
+
+    /// The content of the registry file
+    pub fn render(&self) -> syn::Result<String> {
+        Ok(render_registry(&self.discoverer.discover()?))
+    }
+
+    /// Write the registry file, relative to `OUT_DIR` unless `file` is absolute, and return its path
+    ///
+    /// Cargo is told to rerun the build script when the explored files change
+    pub fn generate(&self, file: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let content = self
+            .render()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let path = registry_path(file.as_ref());
+        fs::write(&path, content)?;
+        for root in &self.roots {
+            println!("cargo:rerun-if-changed={}", root);
+        }
+        Ok(path)
+    }
+}
+
+/// `file` relative to `OUT_DIR`, if it is relative and `OUT_DIR` is set
+pub fn registry_path(file: &Path) -> PathBuf {
+    match env::var_os("OUT_DIR") {
+        Some(out_dir) if file.is_relative() => Path::new(&out_dir).join(file),
+        _ => file.to_path_buf(),
+    }
+}
+
+/// List the discovered items in the registry format, the skipped ones included
+///
+/// ```text
+/// paths(
+///     #[at("src/api.rs", 12)] #[route(get, path = "/users/{id}")] crate::api::get_user,
+///     #[at("src/admin.rs", 3)] #[cfg(feature = "admin")] crate::admin::list_users,
+/// ),
+/// schemas(
+///     #[at("src/models.rs", 8)] #[skipped(generic)] crate::models::Page<T>,
+/// ),
+/// responses(),
+/// modifiers(
+///     #[at("src/security.rs", 4)] #[order(1)] crate::security::SecurityAddon,
+/// ),
+/// nests(
+///     #[at("src/users/mod.rs", 6)] #[nest("/users")] crate::users::UsersApi,
+/// ),
+/// ```
+///
+/// The types referenced by an item are listed with the full paths they may be, as resolved in its module:
+/// `#[reference(body, "User", ["crate::api::User", "crate::models::User"])]`
+pub fn render_registry(items: &[DiscoveredItem]) -> String {
+    let mut content = format!("{}\n", HEADER);
+    for (name, kind) in KINDS {
+        let _ = writeln!(content, "{}(", name);
+        for item in items.iter().filter(|item| item.kind == kind) {
+            let _ = write!(content, "    #[at({:?}, {})] ", item.file, item.line);
+            if let Some(route) = &item.route {
+                let _ = write!(content, "#[route({})] ", render_route(route));
+            }
+            if let Some(cfg) = &item.cfg {
+                let _ = write!(content, "#[cfg({})] ", cfg);
+            }
+            if let Some(nest) = &item.nest {
+                let _ = write!(content, "#[nest({:?})] ", nest);
+            }
+            if let Some(order) = item.order {
+                let _ = write!(content, "#[order({})] ", order);
+            }
+            if let Some(reason) = &item.skip_reason {
+                let _ = write!(content, "#[skipped({})] ", skip_reason_name(reason));
+            }
+            for reference in &item.references {
+                let _ = write!(
+                    content,
+                    "#[reference({}, {:?}, {:?})] ",
+                    reference.position.name(),
+                    reference.name,
+                    reference.candidates
+                );
+            }
+            let _ = writeln!(content, "{},", item.path_string());
+        }
+        content.push_str("),\n");
+    }
+    content
+}
+
+/// `get, head, tagged, path = "/users", context_path = "/api", operation_id = "list_users",
+/// framework = "actix_web::get"`, and `expression_path` or `expression_operation_id` when they aren't string literals
+fn render_route(route: &Route) -> String {
+    let mut entries = route.methods.clone();
+    if route.tagged {
+        entries.push("tagged".to_string());
+    }
+    if route.expression_path {
+        entries.push("expression_path".to_string());
+    }
+    if route.expression_operation_id {
+        entries.push("expression_operation_id".to_string());
+    }
+    let values = [
+        ("path", &route.path),
+        ("context_path", &route.context_path),
+        ("operation_id", &route.operation_id),
+        ("framework", &route.framework),
+    ];
+    for (name, value) in values {
+        if let Some(value) = value {
+            entries.push(format!("{} = {:?}", name, value));
+        }
+    }
+    entries.join(", ")
+}
+
+fn parse_route(attr: &Attribute) -> syn::Result<Route> {
+    let mut route = Route::default();
+    for meta in attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+        match meta {
+            Meta::Path(flag) if flag.is_ident("tagged") => route.tagged = true,
+            Meta::Path(flag) if flag.is_ident("expression_path") => route.expression_path = true,
+            Meta::Path(flag) if flag.is_ident("expression_operation_id") => route.expression_operation_id = true,
+            Meta::Path(method) => route.methods.push(method.require_ident()?.to_string()),
+            Meta::NameValue(entry) => {
+                let Expr::Lit(ExprLit {
+                    lit: Lit::Str(value), ..
+                }) = &entry.value
+                else {
+                    return Err(syn::Error::new_spanned(entry.value, "expected a string"));
+                };
+                let slot = match entry.path.require_ident()?.to_string().as_str() {
+                    "path" => &mut route.path,
+                    "context_path" => &mut route.context_path,
+                    "operation_id" => &mut route.operation_id,
+                    "framework" => &mut route.framework,
+                    _ => return Err(syn::Error::new_spanned(entry.path, "unknown route entry")),
+                };
+                *slot = Some(value.value());
+            }
+            Meta::List(list) => return Err(syn::Error::new_spanned(list, "unknown route entry")),
+        }
+    }
+    Ok(route)
+}
+
+/// `field, "models::User", ["crate::models::User", "models::User"]`
+fn parse_reference(input: ParseStream) -> syn::Result<TypeReference> {
+    let name: Ident = input.parse()?;
+    let Some((_, position)) = Position::NAMES.iter().find(|(p, _)| name == p) else {
+        return Err(syn::Error::new(
+            name.span(),
+            format!("unknown reference position `{}`", name),
+        ));
+    };
+    input.parse::<Token![,]>()?;
+    let written = input.parse::<LitStr>()?.value();
+    input.parse::<Token![,]>()?;
+    let content;
+    bracketed!(content in input);
+    let candidates = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+    Ok(TypeReference {
+        position: *position,
+        name: written,
+        candidates: candidates.iter().map(LitStr::value).collect(),
+    })
+}
+
+/// Read the items of a registry file written by [`Builder::generate`]
+pub fn parse_registry(content: &str) -> syn::Result<Vec<DiscoveredItem>> {
+    let lists = syn::parse_str::<Registry>(content)?;
+    Ok(lists.items)
+}
+
+const KINDS: [(&str, DiscoveredKind); 5] = [
+    ("paths", DiscoveredKind::Path),
+    ("schemas", DiscoveredKind::Schema),
+    ("responses", DiscoveredKind::Response),
+    ("modifiers", DiscoveredKind::Modifier),
+    ("nests", DiscoveredKind::Nest),
+];
+
+const SKIP_REASONS: [(&str, SkipReason); 4] = [
+    ("generic", SkipReason::Generic),
+    ("ignored", SkipReason::Ignored),
+    ("unreachable", SkipReason::Unreachable),
+    ("nested", SkipReason::Nested),
+];
+
+fn skip_reason_name(reason: &SkipReason) -> &'static str {
+    SKIP_REASONS
+        .iter()
+        .find(|(_, r)| r == reason)
+        .map(|(name, _)| *name)
+        .expect("every skip reason has a name")
+}
+
+struct Registry {
+    items: Vec<DiscoveredItem>,
+}
+
+impl Parse for Registry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut items = vec![];
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            let Some((_, kind)) = KINDS.iter().find(|(k, _)| name == k) else {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!(
+                        "unknown list `{}`, expected `paths`, `schemas`, `responses`, `modifiers` or `nests`",
+                        name
+                    ),
+                ));
+            };
+            let content;
+            parenthesized!(content in input);
+            for entry in Punctuated::<Entry, Token![,]>::parse_terminated(&content)? {
+                items.push(entry.into_item(*kind)?);
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self { items })
+    }
+}
+
+/// `#[at("src/api.rs", 12)] #[cfg(...)] #[skipped(generic)] crate::api::get_user`
+struct Entry {
+    attrs: Vec<Attribute>,
+    path: syn::Path,
+}
+
+impl Parse for Entry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            attrs: input.call(Attribute::parse_outer)?,
+            path: input.parse()?,
+        })
+    }
+}
+
+impl Entry {
+    fn into_item(self, kind: DiscoveredKind) -> syn::Result<DiscoveredItem> {
+        let mut item = DiscoveredItem {
+            kind,
+            path: self.path,
+            visibility: Visibility::Inherited,
+            file: String::new(),
+            line: 0,
+            span: Span::call_site(),
+            attrs: vec![],
+            route: None,
+            cfg: None,
+            skip_reason: None,
+            order: None,
+            nest: None,
+            references: vec![],
+            public_module: true,
+        };
+        for attr in self.attrs {
+            if attr.path().is_ident("at") {
+                attr.parse_args_with(|input: ParseStream| {
+                    item.file = input.parse::<LitStr>()?.value();
+                    input.parse::<Token![,]>()?;
+                    item.line = input.parse::<LitInt>()?.base10_parse()?;
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("route") {
+                item.route = Some(parse_route(&attr)?);
+            } else if attr.path().is_ident("cfg") {
+                item.cfg = Some(attr.meta.require_list()?.tokens.clone());
+            } else if attr.path().is_ident("nest") {
+                item.nest = Some(attr.parse_args::<LitStr>()?.value());
+            } else if attr.path().is_ident("order") {
+                item.order = Some(attr.parse_args::<LitInt>()?.base10_parse()?);
+            } else if attr.path().is_ident("reference") {
+                item.references.push(attr.parse_args_with(parse_reference)?);
+            } else if attr.path().is_ident("skipped") {
+                let name: Ident = attr.parse_args()?;
+                let Some((_, reason)) = SKIP_REASONS.iter().find(|(r, _)| name == r) else {
+                    return Err(syn::Error::new(name.span(), format!("unknown skip reason `{}`", name)));
+                };
+                item.skip_reason = Some(reason.clone());
+            } else {
+                return Err(syn::Error::new_spanned(attr, "unknown registry attribute"));
+            }
+        }
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_registry, render_registry, Builder};
+    use crate::discover::{DiscoveredKind, Discoverer, SkipReason};
+
+    #[test]
+    fn test_registry_round_trip() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/users", operation_id = "list", tag = "users")]
+            #[actix_web::get("/users")]
+            pub fn list_users() {}
+
+            #[cfg(feature = "admin")]
+            #[utoipa::path(delete, path = "/users")]
+            pub fn delete_users() {}
+
+            #[derive(utoipa::ToSchema)]
+            pub struct User;
+
+            #[derive(utoipa::ToSchema)]
+            pub struct Page<T>(T);
+        };
+        let items = Discoverer::new().file("./src/api.rs", file).discover().unwrap();
+        let content = render_registry(&items);
+        assert!(content.contains("#[cfg(feature = \"admin\")] crate::api::delete_users,"));
+
+        let parsed = parse_registry(&content).unwrap();
+        assert_eq!(parsed.len(), items.len());
+        for (parsed, item) in parsed.iter().zip(&items) {
+            assert_eq!(parsed.kind, item.kind);
+            assert_eq!(parsed.path_string(), item.path_string());
+            assert_eq!(parsed.file, item.file);
+            assert_eq!(parsed.skip_reason, item.skip_reason);
+            assert_eq!(parsed.route, item.route);
+            assert_eq!(
+                parsed.cfg.as_ref().map(ToString::to_string),
+                item.cfg.as_ref().map(ToString::to_string)
+            );
+        }
+        let page = parsed
+            .iter()
+            .find(|item| item.path_string().starts_with("crate::api::Page"));
+        assert_eq!(page.unwrap().kind, DiscoveredKind::Schema);
+        assert_eq!(page.unwrap().skip_reason, Some(SkipReason::Generic));
+    }
+
+    #[test]
+    fn test_builder_render() {
+        let content = Builder::new()
+            .root("../utoipauto/tests/default_features/controllers/controller1.rs")
+            .render()
+            .unwrap();
+        assert!(content.starts_with("// @generated by utoipauto"));
+        assert!(content.contains("route1,"));
+    }
+
+    #[test]
+    fn test_invalid_registry() {
+        let error = parse_registry("paths(#[at(\"src/api.rs\", 1)] #[skipped(hidden)] crate::api::get_user)")
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "unknown skip reason `hidden`");
+        assert!(parse_registry("routes()").is_err());
+    }
+}
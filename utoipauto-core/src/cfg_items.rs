@@ -0,0 +1,86 @@
+//! The discovered items compiled under a cfg predicate
+//!
+//! The entries of `paths(...)` and `components(...)` take no attributes, so the items of each predicate are listed
+//! by a hidden documentation struct compiled under the predicate. A generated `utoipa::Modify` merges these
+//! documentations into the documentation struct, before the modifiers of `modifiers(...)` run.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Ident};
+
+/// The documentation struct of each predicate, from its `#[openapi(...)]`, and the `utoipa::Modify` merging them,
+/// named `modifier`
+///
+/// The structs are named after the modifier: `__utoipauto_cfg_ApiDoc_0` for `__utoipauto_cfg_ApiDoc`
+pub fn cfg_documentations(modifier: &Ident, documentations: &[(TokenStream, Attribute)]) -> TokenStream {
+    let structs = documentations
+        .iter()
+        .enumerate()
+        .map(|(i, (cfg, openapi))| (quote!(#[cfg(#cfg)]), format_ident!("{}_{}", modifier, i), openapi))
+        .collect::<Vec<_>>();
+    let definitions = structs.iter().map(|(cfg, ident, openapi)| {
+        quote!(
+            #cfg
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #[derive(::utoipa::OpenApi)]
+            #openapi
+            struct #ident;
+        )
+    });
+    let merges = structs
+        .iter()
+        .map(|(cfg, ident, _)| quote!(#cfg openapi.merge(<#ident as ::utoipa::OpenApi>::openapi());));
+    quote!(
+        #(#definitions)*
+
+        #[allow(non_camel_case_types)]
+        struct #modifier;
+
+        impl ::utoipa::Modify for #modifier {
+            fn modify(&self, openapi: &mut ::utoipa::openapi::OpenApi) {
+                #(#merges)*
+            }
+        }
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+
+    use super::cfg_documentations;
+
+    #[test]
+    fn test_cfg_documentations() {
+        let modifier = syn::parse_quote!(__utoipauto_cfg_ApiDoc);
+        let documentations = [
+            (
+                quote!(feature = "admin"),
+                syn::parse_quote!(#[openapi(paths(crate::admin::list_users))]),
+            ),
+            (quote!(unix), syn::parse_quote!(#[openapi(paths(crate::unix::status))])),
+        ];
+        let code = cfg_documentations(&modifier, &documentations).to_string();
+        assert!(code.contains(
+            &quote!(
+                #[cfg(feature = "admin")]
+                #[doc(hidden)]
+                #[allow(non_camel_case_types)]
+                #[derive(::utoipa::OpenApi)]
+                #[openapi(paths(crate::admin::list_users))]
+                struct __utoipauto_cfg_ApiDoc_0;
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote!(
+                #[cfg(feature = "admin")]
+                openapi.merge(<__utoipauto_cfg_ApiDoc_0 as ::utoipa::OpenApi>::openapi());
+                #[cfg(unix)]
+                openapi.merge(<__utoipauto_cfg_ApiDoc_1 as ::utoipa::OpenApi>::openapi());
+            )
+            .to_string()
+        ));
+    }
+}
@@ -0,0 +1,427 @@
+//! Find the route handlers of the web frameworks that are not documented
+//!
+//! A function is a handler if it has a route attribute, `#[get("/users")]` with actix or rocket,
+//! or if it is registered with a router: `Router::new().route("/users", get(list_users))` with axum,
+//! `web::resource("/users").route(web::get().to(list_users))` with actix.
+
+use std::fmt;
+
+use proc_macro2::{Span, TokenStream};
+use syn::visit::{self, Visit};
+use syn::{Attribute, Expr, ExprCall, ExprMethodCall, Lit};
+
+use crate::route_utils::{Route, HTTP_METHODS};
+use crate::token_utils::warning;
+
+/// The attribute marking a handler that is intentionally left out of the documentation
+pub const ALLOW_ATTRIBUTE: &str = "utoipa_undocumented";
+
+/// A route handler without the function attribute of the documented paths
+#[derive(Clone)]
+pub struct UndocumentedHandler {
+    /// The rust path of the function: `crate::api::get_user`
+    pub path: syn::Path,
+    pub file: String,
+    /// The line of the function in `file`, 0 if unknown
+    pub line: usize,
+    pub span: Span,
+    /// The `#[cfg(...)]` predicate the function, or one of its modules, is compiled under
+    pub cfg: Option<TokenStream>,
+    /// Why the function is a handler
+    pub evidence: Evidence,
+}
+
+/// Why a function is considered as a route handler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Evidence {
+    /// The function has the route attribute of a web framework: `#[get("/users")]`
+    RouteAttribute(Route),
+    /// The function is registered with a router method: `.route("/users", get(list_users))`
+    Registered {
+        /// `route` or `to`
+        method: String,
+        /// The route path given to the router, if it is a string literal
+        route: Option<String>,
+        /// The file the function is registered in
+        file: String,
+    },
+}
+
+impl fmt::Display for Evidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Evidence::RouteAttribute(route) => write!(
+                f,
+                "it has the route attribute `#[{}({:?})]`",
+                route.methods.first().map(String::as_str).unwrap_or_default(),
+                route.path.as_deref().unwrap_or_default()
+            ),
+            Evidence::Registered { method, route, file } => match route {
+                Some(route) => write!(f, "it is registered with `.{}({:?}, ...)` in {}", method, route, file),
+                None => write!(f, "it is registered with `.{}(...)` in {}", method, file),
+            },
+        }
+    }
+}
+
+impl UndocumentedHandler {
+    /// `./src/lib.rs:12`, or only the file if the line is unknown
+    pub fn location(&self) -> String {
+        match self.line {
+            0 => self.file.clone(),
+            line => format!("{}:{}", self.file, line),
+        }
+    }
+}
+
+/// A function found while exploring, that may be a handler
+#[derive(Clone)]
+pub(crate) struct Function {
+    pub(crate) path: syn::Path,
+    pub(crate) file: String,
+    pub(crate) line: usize,
+    pub(crate) span: Span,
+    pub(crate) cfg: Option<TokenStream>,
+    /// The route of the function, from its attributes
+    pub(crate) route: Option<Route>,
+    /// Classified by a discovery rule, as a path or as ignored
+    pub(crate) classified: bool,
+    /// Marked with `#[utoipa_undocumented]`
+    pub(crate) allowed: bool,
+}
+
+/// `true` if the attributes allow the function to stay undocumented
+pub(crate) fn is_allowed(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().segments.last().is_some_and(|s| s.ident == ALLOW_ATTRIBUTE))
+}
+
+/// A function registered with a router, as written in the source: `get(users::list)`
+#[derive(Clone)]
+pub(crate) struct Registration {
+    /// The segments of the function path, as written
+    segments: Vec<String>,
+    /// The module the function is registered in
+    module: String,
+    method: String,
+    route: Option<String>,
+    file: String,
+}
+
+/// Find the functions registered with a router in a file
+pub(crate) fn registrations(module: &str, file_path: &str, file: &syn::File) -> Vec<Registration> {
+    let mut visitor = RouterVisitor {
+        module,
+        file: file_path,
+        registrations: vec![],
+    };
+    visitor.visit_file(file);
+    visitor.registrations
+}
+
+/// The handlers that are neither classified by a discovery rule nor allowed with `#[utoipa_undocumented]`
+pub(crate) fn undocumented(functions: &[Function], registrations: &[Registration]) -> Vec<UndocumentedHandler> {
+    let paths = functions
+        .iter()
+        .map(|function| path_segments(&function.path))
+        .collect::<Vec<_>>();
+    let mut handlers: Vec<UndocumentedHandler> = vec![];
+    let push = |index: usize, evidence: Evidence, handlers: &mut Vec<UndocumentedHandler>| {
+        let function = &functions[index];
+        if function.classified || function.allowed || handlers.iter().any(|h| path_segments(&h.path) == paths[index]) {
+            return;
+        }
+        handlers.push(UndocumentedHandler {
+            path: function.path.clone(),
+            file: function.file.clone(),
+            line: function.line,
+            span: function.span,
+            cfg: function.cfg.clone(),
+            evidence,
+        });
+    };
+
+    for (index, function) in functions.iter().enumerate() {
+        if let Some(route) = &function.route {
+            push(index, Evidence::RouteAttribute(route.clone()), &mut handlers);
+        }
+    }
+    for registration in registrations {
+        if let Some(index) = resolve(&paths, registration) {
+            let evidence = Evidence::Registered {
+                method: registration.method.clone(),
+                route: registration.route.clone(),
+                file: registration.file.clone(),
+            };
+            push(index, evidence, &mut handlers);
+        }
+    }
+    handlers
+}
+
+/// A compiler warning for each undocumented handler
+pub fn undocumented_warnings(handlers: &[UndocumentedHandler], fn_attribute_name: &str) -> TokenStream {
+    let attribute = match fn_attribute_name {
+        "utoipa" => "#[utoipa::path]".to_string(),
+        name => format!("#[{}]", name),
+    };
+    handlers
+        .iter()
+        .map(|handler| {
+            let message = format!(
+                "utoipauto: the handler `{}` ({}) is not documented: {} but no {} attribute, \
+                 mark it with #[{}] if it is intentional",
+                path_segments(&handler.path).join("::"),
+                handler.location(),
+                handler.evidence,
+                attribute,
+                ALLOW_ATTRIBUTE
+            );
+            warning(&message, handler.span, handler.cfg.as_ref(), false)
+        })
+        .collect()
+}
+
+/// Find the function a registration points to
+///
+/// `crate::`, `self::` and `super::` paths are resolved from the module of the registration,
+/// other paths are tried relative to this module, then matched by their last segments, if only one function matches
+fn resolve(paths: &[Vec<String>], registration: &Registration) -> Option<usize> {
+    let mut module: Vec<String> = registration.module.split("::").map(str::to_string).collect();
+    let mut segments = registration.segments.as_slice();
+    let absolute = match segments.first().map(String::as_str) {
+        Some("crate") => Some(segments.to_vec()),
+        Some("self") | Some("super") => {
+            while let Some(first) = segments.first() {
+                match first.as_str() {
+                    "self" => {}
+                    "super" => {
+                        module.pop();
+                    }
+                    _ => break,
+                }
+                segments = &segments[1..];
+            }
+            Some([module.as_slice(), segments].concat())
+        }
+        _ => None,
+    };
+    if let Some(absolute) = absolute {
+        return paths.iter().position(|path| *path == absolute);
+    }
+
+    let relative = [module.as_slice(), segments].concat();
+    if let Some(index) = paths.iter().position(|path| *path == relative) {
+        return Some(index);
+    }
+    let mut matches = paths.iter().enumerate().filter(|(_, path)| path.ends_with(segments));
+    match (matches.next(), matches.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
+
+fn path_segments(path: &syn::Path) -> Vec<String> {
+    path.segments.iter().map(|segment| segment.ident.to_string()).collect()
+}
+
+/// Collect the functions given to `.route(...)`, and to `.to(...)` on a `web::resource(...)`
+struct RouterVisitor<'a> {
+    module: &'a str,
+    file: &'a str,
+    registrations: Vec<Registration>,
+}
+
+impl RouterVisitor<'_> {
+    fn register(&mut self, handler: &Expr, method: &str, route: Option<String>) {
+        let Expr::Path(handler) = handler else { return };
+        self.registrations.push(Registration {
+            segments: path_segments(&handler.path),
+            module: self.module.to_string(),
+            method: method.to_string(),
+            route,
+            file: self.file.to_string(),
+        });
+    }
+
+    /// The handlers of a method router: `get(list).post(create)`, `web::get().to(list)`
+    fn register_method_router(&mut self, expr: &Expr, route: &Option<String>) {
+        match expr {
+            Expr::Call(call) if is_method_function(call) => {
+                if let [handler] = call.args.iter().collect::<Vec<_>>()[..] {
+                    self.register(handler, "route", route.clone());
+                }
+            }
+            Expr::MethodCall(call) => {
+                let method = call.method.to_string();
+                if HTTP_METHODS.contains(&method.as_str()) || method == "to" {
+                    if let [handler] = call.args.iter().collect::<Vec<_>>()[..] {
+                        self.register(handler, "route", route.clone());
+                    }
+                }
+                self.register_method_router(&call.receiver, route);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for RouterVisitor<'_> {
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        match call.method.to_string().as_str() {
+            // axum: `.route("/users", get(list))`, actix: `.route("/users", web::get().to(list))` or `.route(web::get().to(list))`
+            "route" => {
+                let args = call.args.iter().collect::<Vec<_>>();
+                let (route, router) = match args[..] {
+                    [path, router] => (string_literal(path), router),
+                    [router] => (resource_path(&call.receiver), router),
+                    _ => return visit::visit_expr_method_call(self, call),
+                };
+                self.register_method_router(router, &route);
+            }
+            // actix: `web::resource("/users").to(list)`
+            "to" => {
+                if let (Some(route), Some(handler)) = (resource_path(&call.receiver), call.args.first()) {
+                    self.register(handler, "to", Some(route));
+                }
+            }
+            _ => {}
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+}
+
+/// `get(list)`, `axum::routing::post(create)`, `any(fallback)`
+fn is_method_function(call: &ExprCall) -> bool {
+    let Expr::Path(func) = &*call.func else { return false };
+    func.path
+        .segments
+        .last()
+        .is_some_and(|segment| HTTP_METHODS.contains(&segment.ident.to_string().as_str()) || segment.ident == "any")
+}
+
+/// The path of the `web::resource("/users")` call a method chain starts with
+fn resource_path(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::MethodCall(call) => resource_path(&call.receiver),
+        Expr::Call(call) => {
+            let Expr::Path(func) = &*call.func else { return None };
+            if !func
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "resource")
+            {
+                return None;
+            }
+            call.args.first().and_then(string_literal)
+        }
+        _ => None,
+    }
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(value) => Some(value.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discover::Discoverer;
+
+    fn undocumented(file: syn::File) -> Vec<(String, String)> {
+        Discoverer::new()
+            .file("./src/api.rs", file)
+            .coverage()
+            .explore()
+            .unwrap()
+            .undocumented
+            .iter()
+            .map(|handler| {
+                let path = handler
+                    .path
+                    .segments
+                    .iter()
+                    .map(|s| s.ident.to_string())
+                    .collect::<Vec<_>>();
+                (path.join("::"), handler.evidence.to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_route_attributes() {
+        let handlers = undocumented(syn::parse_quote! {
+            #[utoipa::path(get, path = "/users")]
+            #[get("/users")]
+            pub async fn list_users() {}
+
+            #[actix_web::post("/users")]
+            pub async fn create_user() {}
+
+            #[utoipa_undocumented]
+            #[get("/health")]
+            pub async fn health() {}
+
+            #[utoipa_ignore]
+            #[utoipa::path(get, path = "/internal")]
+            #[get("/internal")]
+            pub async fn internal() {}
+        });
+        assert_eq!(
+            handlers,
+            vec![(
+                "crate::api::create_user".to_string(),
+                "it has the route attribute `#[post(\"/users\")]`".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_registered_handlers() {
+        let handlers = undocumented(syn::parse_quote! {
+            pub fn router() -> Router {
+                Router::new()
+                    .route("/users", get(list_users).post(self::create_user))
+                    .route("/health", axum::routing::get(health))
+            }
+
+            pub fn configure(cfg: &mut web::ServiceConfig) {
+                cfg.service(web::resource("/pets").route(web::get().to(list_pets)))
+                    .service(web::resource("/pets/{id}").to(crate::api::get_pet));
+            }
+
+            #[utoipa::path(get, path = "/users")]
+            pub async fn list_users() {}
+            pub async fn create_user() {}
+            #[utoipa_undocumented]
+            pub async fn health() {}
+            pub async fn list_pets() {}
+            pub async fn get_pet() {}
+            pub async fn unused() {}
+        });
+        assert_eq!(
+            handlers,
+            vec![
+                (
+                    "crate::api::create_user".to_string(),
+                    "it is registered with `.route(\"/users\", ...)` in ./src/api.rs".to_string()
+                ),
+                (
+                    "crate::api::list_pets".to_string(),
+                    "it is registered with `.route(\"/pets\", ...)` in ./src/api.rs".to_string()
+                ),
+                (
+                    "crate::api::get_pet".to_string(),
+                    "it is registered with `.to(\"/pets/{id}\", ...)` in ./src/api.rs".to_string()
+                ),
+            ]
+        );
+    }
+}
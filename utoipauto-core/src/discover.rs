@@ -1,180 +1,1155 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::vec;
 
-use crate::file_utils::{extract_module_name_from_path, parse_files};
-use crate::token_utils::Parameters;
-use quote::ToTokens;
-use syn::token::Comma;
-use syn::Ident;
-use syn::{punctuated::Punctuated, Attribute, GenericParam, Item, ItemFn, ItemImpl, Meta, Token};
+use crate::coverage::{is_allowed, registrations, undocumented, Function, Registration, UndocumentedHandler};
+use crate::file_utils::{extract_module_name_from_path, read_files, LineIndex, SourceFile};
+use crate::references::{field_references, handler_references, Imports, TypeReference};
+use crate::route_utils::Route;
+use crate::rules::{Classification, DiscoveryRule, RuleItem, UtoipaRule};
+use crate::string_utils::closest_match;
+use crate::token_utils::{combine_error, warning, Parameters};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::spanned::Spanned;
+use syn::{Attribute, GenericParam, Generics, Item, ItemImpl, ItemMod, Meta, Visibility};
 
-/// Discover everything from a file, will explore folder recursively
-pub fn discover_from_file(
-    src_path: String,
-    crate_name: String,
-    params: &Parameters,
-) -> (Vec<syn::Path>, Vec<syn::Path>, Vec<syn::Path>) {
-    let files = parse_files(&src_path).unwrap_or_else(|_| panic!("Failed to parse file {}", src_path));
-
-    files
-        .into_iter()
-        .map(|e| parse_module_items(extract_module_name_from_path(&e.0, &crate_name), e.1.items, params))
-        .fold(Vec::<DiscoverType>::new(), |mut acc, mut v| {
-            acc.append(&mut v);
-            acc
-        })
-        .into_iter()
-        .fold(
-            (
-                Vec::<syn::Path>::new(),
-                Vec::<syn::Path>::new(),
-                Vec::<syn::Path>::new(),
-            ),
-            |mut acc, v| {
-                match v {
-                    DiscoverType::Fn(n) => acc.0.push(n),
-                    DiscoverType::Model(n) => acc.1.push(n),
-                    DiscoverType::Response(n) => acc.2.push(n),
-                    DiscoverType::CustomModelImpl(n) => acc.1.push(n),
-                    DiscoverType::CustomResponseImpl(n) => acc.2.push(n),
-                };
+/// What a discovered item documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoveredKind {
+    /// A function with the `#[utoipa::path]` attribute
+    Path,
+    /// A type deriving or implementing `ToSchema`
+    Schema,
+    /// A type deriving or implementing `ToResponse`
+    Response,
+    /// A unit struct marked with `#[utoipauto::modifier]`
+    Modifier,
+    /// A documentation struct marked with `#[utoipauto::nest]`
+    Nest,
+}
 
-                acc
-            },
-        )
+/// Why a discovered item is left out of the documentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The item has type or const generic parameters, which can't be registered as is
+    Generic,
+    /// The item is marked with `#[utoipa_ignore]`, or ignored by a discovery rule
+    Ignored,
+    /// The file of the item is never declared with `mod` by its parent module
+    Unreachable,
+    /// The handler or the modifier belongs to the nested documentation struct of its module
+    Nested,
+}
+
+impl fmt::Display for DiscoveredKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoveredKind::Path => write!(f, "path"),
+            DiscoveredKind::Schema => write!(f, "schema"),
+            DiscoveredKind::Response => write!(f, "response"),
+            DiscoveredKind::Modifier => write!(f, "modifier"),
+            DiscoveredKind::Nest => write!(f, "nested documentation"),
+        }
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Generic => write!(f, "it has generic parameters"),
+            SkipReason::Ignored => write!(f, "it is ignored with #[utoipa_ignore] or a discovery rule"),
+            SkipReason::Unreachable => write!(f, "its module is never declared by its parent module"),
+            SkipReason::Nested => write!(f, "it belongs to the nested documentation of its module"),
+        }
+    }
+}
+
+/// An item found while exploring the source files
+#[derive(Clone)]
+pub struct DiscoveredItem {
+    pub kind: DiscoveredKind,
+    pub path: syn::Path,
+    pub visibility: Visibility,
+    /// The file the item was found in
+    pub file: String,
+    /// The line of the item in `file`, 0 if unknown
+    pub line: usize,
+    /// The span of the item name, as parsed from `file`
+    pub span: Span,
+    /// The attributes of the item, or of the impl block
+    pub attrs: Vec<Attribute>,
+    /// The route of a path item
+    pub route: Option<Route>,
+    /// The `#[cfg(...)]` predicate the item, or one of its modules, is compiled under
+    pub cfg: Option<TokenStream>,
+    /// Why the item is left out of the documentation, if it is
+    pub skip_reason: Option<SkipReason>,
+    /// The `order` of a modifier given with `#[utoipauto::modifier(order = 1)]`
+    pub order: Option<i32>,
+    /// The path a nested documentation struct is nested under: `/users`
+    pub nest: Option<String>,
+    /// The types referenced by a handler, or by the fields of a struct or an enum
+    pub references: Vec<TypeReference>,
+    /// `true` if the module of the item and all its parents are declared `pub`, so that another crate can name it
+    pub public_module: bool,
+}
+
+impl DiscoveredItem {
+    /// `true` if the item should be added to the documentation
+    pub fn is_discovered(&self) -> bool {
+        self.skip_reason.is_none()
+    }
+
+    /// `true` if the item can be compiled together with `other`: they have the same cfg predicate, or one of them
+    /// has none
+    pub fn compiled_with(&self, other: &DiscoveredItem) -> bool {
+        match (&self.cfg, &other.cfg) {
+            (Some(cfg), Some(other)) => cfg.to_string() == other.to_string(),
+            _ => true,
+        }
+    }
+
+    /// The rust path of the item, without spaces: `crate::api::get_user`
+    pub fn path_string(&self) -> String {
+        path_to_string(&self.path)
+    }
+
+    /// `./src/lib.rs:12`, or only the file if the line is unknown
+    pub fn location(&self) -> String {
+        match self.line {
+            0 => self.file.clone(),
+            line => format!("{}:{}", self.file, line),
+        }
+    }
+}
+
+/// A module found while exploring the source files
+#[derive(Clone)]
+pub struct DiscoveredModule {
+    /// `crate::api::users`
+    pub path: syn::Path,
+    /// The `///` doc comments of its `mod` declaration, then the `//!` ones of the module
+    pub doc: Option<String>,
+}
+
+/// The result of exploring the source files
+#[derive(Default)]
+pub struct Discovery {
+    pub items: Vec<DiscoveredItem>,
+    /// The explored modules, in the order they are declared
+    pub modules: Vec<DiscoveredModule>,
+    /// The discovery decisions about the item given to `explain = "..."`
+    pub explanation: Vec<String>,
+    /// Every item path seen while exploring, to suggest a path when the explained item isn't found
+    pub seen: Vec<String>,
+    /// The route handlers without the function attribute, found with [`Discoverer::coverage`]
+    pub undocumented: Vec<UndocumentedHandler>,
+    /// The functions and the router registrations, to find the undocumented handlers
+    pub(crate) functions: Vec<Function>,
+    pub(crate) registrations: Vec<Registration>,
+    /// Module path => position of its `mod` declaration among the ones of its parent
+    pub(crate) positions: HashMap<String, usize>,
+    /// The modules of the explored files, with their `//!` docs, merged after the `mod` declarations
+    pub(crate) module_files: Vec<DiscoveredModule>,
+}
+
+impl Discovery {
+    pub fn extend(&mut self, other: Discovery) {
+        self.items.extend(other.items);
+        self.modules.extend(other.modules);
+        self.explanation.extend(other.explanation);
+        self.seen.extend(other.seen);
+        self.undocumented.extend(other.undocumented);
+        self.functions.extend(other.functions);
+        self.registrations.extend(other.registrations);
+        self.positions.extend(other.positions);
+        self.module_files.extend(other.module_files);
+    }
+
+    /// Sort the modules in declaration order, merging the docs found for the same module
+    ///
+    /// The modules whose parent wasn't explored come last, in the order of their sources and files
+    fn sort_modules(&mut self) {
+        let mut sorted: Vec<DiscoveredModule> = vec![];
+        let files = std::mem::take(&mut self.module_files);
+        for module in std::mem::take(&mut self.modules).into_iter().chain(files) {
+            let path = path_to_string(&module.path);
+            match sorted.iter_mut().find(|m| path_to_string(&m.path) == path) {
+                Some(existing) => {
+                    existing.doc = match (existing.doc.take(), module.doc) {
+                        (Some(outer), Some(inner)) => Some(format!("{}\n\n{}", outer, inner)),
+                        (outer, inner) => outer.or(inner),
+                    }
+                }
+                None => sorted.push(module),
+            }
+        }
+        sorted.sort_by_cached_key(|module| self.position_of(&path_to_string(&module.path)));
+        self.modules = sorted;
+    }
+
+    /// The position of the declaration of a module and of each of its parents
+    fn position_of(&self, module_path: &str) -> Vec<usize> {
+        let mut segments = module_path.split("::");
+        let mut prefix = segments.next().unwrap_or_default().to_string();
+        segments
+            .map(|segment| {
+                prefix = format!("{}::{}", prefix, segment);
+                self.positions.get(&prefix).copied().unwrap_or(usize::MAX)
+            })
+            .collect()
+    }
+}
+
+/// Group the discovered items by the cfg predicate they are compiled under
+///
+/// The items compiled unconditionally come first, in a `(None, items)` group, then one group for each distinct
+/// predicate, in the order they were discovered
+pub fn cfg_groups<'a>(
+    items: impl IntoIterator<Item = &'a DiscoveredItem>,
+) -> Vec<(Option<TokenStream>, Vec<&'a DiscoveredItem>)> {
+    let mut groups: Vec<(Option<TokenStream>, Vec<&DiscoveredItem>)> = vec![(None, vec![])];
+    for item in items {
+        let key = item.cfg.as_ref().map(ToString::to_string);
+        match groups
+            .iter_mut()
+            .find(|(cfg, _)| cfg.as_ref().map(ToString::to_string) == key)
+        {
+            Some((_, group)) => group.push(item),
+            None => groups.push((item.cfg.clone(), vec![item])),
+        }
+    }
+    groups
 }
 
-#[allow(unused)]
-enum DiscoverType {
-    Fn(syn::Path),
-    Model(syn::Path),
-    Response(syn::Path),
-    CustomModelImpl(syn::Path),
-    CustomResponseImpl(syn::Path),
+/// The `UTOIPAUTO_REPORT` constant of the documentation struct, listing every discovered item
+pub fn report(openapi_macro: &syn::ItemStruct, items: &[DiscoveredItem]) -> TokenStream {
+    let report = quote!(::utoipauto::report);
+    let entries = items.iter().map(|item| {
+        let kind = match item.kind {
+            DiscoveredKind::Path => quote!(#report::DiscoveredKind::Path),
+            DiscoveredKind::Schema => quote!(#report::DiscoveredKind::Schema),
+            DiscoveredKind::Response => quote!(#report::DiscoveredKind::Response),
+            DiscoveredKind::Modifier => quote!(#report::DiscoveredKind::Modifier),
+            DiscoveredKind::Nest => quote!(#report::DiscoveredKind::Nest),
+        };
+        let skip_reason = match (&item.skip_reason, &item.cfg) {
+            (Some(SkipReason::Generic), _) => quote!(Some(#report::SkipReason::Generic)),
+            (Some(SkipReason::Ignored), _) => quote!(Some(#report::SkipReason::Ignored)),
+            (Some(SkipReason::Unreachable), _) => quote!(Some(#report::SkipReason::Unreachable)),
+            (Some(SkipReason::Nested), _) => quote!(Some(#report::SkipReason::Nested)),
+            (None, Some(cfg)) => {
+                let predicate = cfg.to_string();
+                quote!(if cfg!(#cfg) { None } else { Some(#report::SkipReason::Cfg(#predicate)) })
+            }
+            (None, None) => quote!(None),
+        };
+        let path = item.path_string();
+        let file = &item.file;
+        let line = proc_macro2::Literal::u32_unsuffixed(item.line as u32);
+        quote!(#report::DiscoveredItem {
+            kind: #kind,
+            path: #path,
+            file: #file,
+            line: #line,
+            skip_reason: #skip_reason,
+        })
+    });
+
+    let ident = &openapi_macro.ident;
+    let (impl_generics, ty_generics, where_clause) = openapi_macro.generics.split_for_impl();
+    quote!(
+        #[allow(dead_code)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Every item found by `#[utoipauto]`, including the ones left out of the documentation
+            pub const UTOIPAUTO_REPORT: &'static [#report::DiscoveredItem] = &[#(#entries),*];
+        }
+    )
 }
 
-fn parse_module_items(module_path: syn::Path, items: Vec<Item>, params: &Parameters) -> Vec<DiscoverType> {
+/// A compiler warning for each item that is left out of the documentation without being ignored
+pub fn skipped_items_warnings(items: &[DiscoveredItem]) -> TokenStream {
     items
-        .into_iter()
-        .filter(|e| {
-            matches!(
-                e,
-                Item::Mod(_) | Item::Fn(_) | Item::Struct(_) | Item::Enum(_) | Item::Impl(_)
-            )
+        .iter()
+        .filter_map(|item| {
+            let what = format!(
+                "utoipauto: the {} `{}` ({}) is not documented",
+                item.kind,
+                item.path_string(),
+                item.location()
+            );
+            match (&item.skip_reason, &item.cfg) {
+                (Some(SkipReason::Ignored | SkipReason::Nested), _) => None,
+                (Some(reason), cfg) => Some(warning(
+                    &format!("{} because {}", what, reason),
+                    item.span,
+                    cfg.as_ref(),
+                    false,
+                )),
+                (None, Some(cfg)) => Some(warning(
+                    &format!("{} because it is only compiled with `cfg({})`", what, cfg),
+                    item.span,
+                    Some(cfg),
+                    true,
+                )),
+                (None, None) => None,
+            }
         })
-        .map(|v| match v {
-            Item::Mod(m) => m.content.map_or(Vec::<DiscoverType>::new(), |cs| {
-                parse_module_items(build_path(&module_path, &m.ident), cs.1, params)
-            }),
-            Item::Fn(f) => parse_function(&f, &params.fn_attribute_name)
-                .into_iter()
-                .map(|item| DiscoverType::Fn(build_path(&module_path, &item)))
+        .collect()
+}
+
+/// A compiler note listing every discovery decision about the item given to `explain = "..."`
+pub fn explain_note(params: &Parameters, discovery: &Discovery) -> TokenStream {
+    let Some((target, span)) = &params.explain else {
+        return TokenStream::new();
+    };
+    let message = if discovery.explanation.is_empty() {
+        let explored = match &params.include {
+            Some((file, _)) => format!("`{}`", file),
+            None => params
+                .paths
+                .iter()
+                .map(|path| format!("`{}`", path.path))
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        let candidates = discovery.seen.iter().map(String::as_str).collect::<Vec<_>>();
+        let suggestion = match closest_match(target, &candidates) {
+            Some(suggestion) => format!(", did you mean `{}`?", suggestion),
+            None => String::new(),
+        };
+        format!(
+            "utoipauto: explain `{}`: no function, struct, enum or impl with this path was found in {}{}",
+            target, explored, suggestion
+        )
+    } else {
+        let decisions = discovery
+            .explanation
+            .iter()
+            .map(|decision| format!("\n  - {}", decision))
+            .collect::<String>();
+        format!("utoipauto: explain `{}`:{}", target, decisions)
+    };
+    warning(&message, *span, None, false)
+}
+
+/// Split the discovered items into the paths, schemas and responses lists of the `#[openapi]` macro
+pub fn to_token_streams<'a>(
+    items: impl IntoIterator<Item = &'a DiscoveredItem>,
+) -> (TokenStream, TokenStream, TokenStream) {
+    let mut uto_paths = Vec::new();
+    let mut uto_models = Vec::new();
+    let mut uto_responses = Vec::new();
+    for item in items.into_iter().filter(|item| item.is_discovered()) {
+        match item.kind {
+            DiscoveredKind::Path => uto_paths.push(&item.path),
+            DiscoveredKind::Schema => uto_models.push(&item.path),
+            DiscoveredKind::Response => uto_responses.push(&item.path),
+            // Added to `modifiers(...)` and `nest(...)` on their own
+            DiscoveredKind::Modifier | DiscoveredKind::Nest => {}
+        }
+    }
+    // We need to add a coma after each path
+    (
+        quote!(#(#uto_paths),*),
+        quote!(#(#uto_models),*),
+        quote!(#(#uto_responses),*),
+    )
+}
+
+/// Explore source files to find the items to document
+///
+/// This is what `#[utoipauto]` uses, and it can also be used from build scripts, CLIs or tests.
+/// Files can be read from the disk with [`Discoverer::root`], or given already parsed with [`Discoverer::file`]:
+///
+/// ```
+/// use utoipauto_core::discover::{DiscoveredKind, Discoverer};
+///
+/// let file: syn::File = syn::parse_quote! {
+///     #[utoipa::path(get, path = "/users")]
+///     pub fn list_users() {}
+///
+///     #[derive(utoipa::ToSchema)]
+///     pub struct User;
+/// };
+/// let items = Discoverer::new()
+///     .file("./src/api/users.rs", file)
+///     .filter(|item| item.kind == DiscoveredKind::Path)
+///     .discover()
+///     .unwrap();
+///
+/// assert_eq!(items.len(), 1);
+/// assert_eq!(items[0].path_string(), "crate::api::users::list_users");
+/// assert_eq!(items[0].route.as_ref().unwrap().path.as_deref(), Some("/users"));
+/// ```
+#[derive(Default)]
+pub struct Discoverer {
+    sources: Vec<Source>,
+    filters: Vec<ItemFilter>,
+    utoipa_rule: UtoipaRule,
+    rules: Vec<Box<dyn DiscoveryRule>>,
+    explain: Option<String>,
+    coverage: bool,
+}
+
+/// Keep the items for which it returns `true`
+type ItemFilter = Box<dyn Fn(&DiscoveredItem) -> bool>;
+
+/// A file or a folder to explore
+struct Source {
+    path: String,
+    /// The content of the file, if it was given already parsed
+    file: Option<syn::File>,
+    crate_name: String,
+    /// Where the errors about this source are reported
+    span: Span,
+}
+
+impl From<&Parameters> for Discoverer {
+    fn from(params: &Parameters) -> Self {
+        Self {
+            sources: params
+                .paths
+                .iter()
+                .map(|path| Source {
+                    path: path.path.clone(),
+                    file: None,
+                    crate_name: path.crate_name.clone(),
+                    span: path.span,
+                })
                 .collect(),
-            Item::Struct(s) => parse_from_attr(&s.attrs, build_path(&module_path, &s.ident), s.generics.params, params),
-            Item::Enum(e) => parse_from_attr(&e.attrs, build_path(&module_path, &e.ident), e.generics.params, params),
-            Item::Impl(im) => parse_from_impl(&im, &module_path, params),
-            _ => vec![],
-        })
-        .fold(Vec::<DiscoverType>::new(), |mut acc, mut v| {
-            acc.append(&mut v);
-            acc
+            filters: vec![],
+            utoipa_rule: UtoipaRule {
+                fn_attribute_name: params.fn_attribute_name.clone(),
+                schema_attribute_name: params.schema_attribute_name.clone(),
+                response_attribute_name: params.response_attribute_name.clone(),
+            },
+            rules: vec![],
+            explain: params.explain.as_ref().map(|(path, _)| path.clone()),
+            coverage: params.coverage,
+        }
+    }
+}
+
+impl Discoverer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Explore a file, or a folder recursively, of the current crate: `./src/api`
+    pub fn root(self, path: impl Into<String>) -> Self {
+        self.crate_root(path, "crate")
+    }
+
+    /// Explore a file, or a folder recursively, whose items are reachable from `crate_name`
+    pub fn crate_root(mut self, path: impl Into<String>, crate_name: impl Into<String>) -> Self {
+        self.sources.push(Source {
+            path: path.into(),
+            file: None,
+            crate_name: crate_name.into(),
+            span: Span::call_site(),
+        });
+        self
+    }
+
+    /// Explore an already parsed file of the current crate
+    ///
+    /// `path` is only used to find the module of the file: `./src/api/users.rs` is `crate::api::users`
+    pub fn file(self, path: impl Into<String>, file: syn::File) -> Self {
+        self.crate_file(path, "crate", file)
+    }
+
+    /// Explore an already parsed file whose items are reachable from `crate_name`
+    pub fn crate_file(mut self, path: impl Into<String>, crate_name: impl Into<String>, file: syn::File) -> Self {
+        self.sources.push(Source {
+            path: path.into(),
+            file: Some(file),
+            crate_name: crate_name.into(),
+            span: Span::call_site(),
+        });
+        self
+    }
+
+    /// Only keep the items for which `filter` returns `true`
+    pub fn filter(mut self, filter: impl Fn(&DiscoveredItem) -> bool + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// The attribute of the functions to document, `utoipa` by default
+    pub fn function_attribute_name(mut self, name: impl Into<String>) -> Self {
+        self.utoipa_rule.fn_attribute_name = name.into();
+        self
+    }
+
+    /// The derive or trait of the schemas to document, `ToSchema` by default
+    pub fn schema_attribute_name(mut self, name: impl Into<String>) -> Self {
+        self.utoipa_rule.schema_attribute_name = name.into();
+        self
+    }
+
+    /// The derive or trait of the responses to document, `ToResponse` by default
+    pub fn response_attribute_name(mut self, name: impl Into<String>) -> Self {
+        self.utoipa_rule.response_attribute_name = name.into();
+        self
+    }
+
+    /// Add a discovery rule, on top of the default utoipa rule
+    pub fn rule(mut self, rule: impl DiscoveryRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Add several discovery rules, like the ones of a config file read with [`MarkerRule::read_config`]
+    ///
+    /// [`MarkerRule::read_config`]: crate::rules::MarkerRule::read_config
+    pub fn rules<R: DiscoveryRule + 'static>(mut self, rules: impl IntoIterator<Item = R>) -> Self {
+        self.rules
+            .extend(rules.into_iter().map(|rule| Box::new(rule) as Box<dyn DiscoveryRule>));
+        self
+    }
+
+    /// The default utoipa rule, then the added rules
+    fn all_rules(&self) -> impl Iterator<Item = &dyn DiscoveryRule> {
+        std::iter::once(&self.utoipa_rule as &dyn DiscoveryRule).chain(self.rules.iter().map(|rule| rule.as_ref()))
+    }
+
+    /// Record every discovery decision about the item with this path in [`Discovery::explanation`]
+    pub fn explain(mut self, path: impl Into<String>) -> Self {
+        self.explain = Some(path.into().replace(' ', ""));
+        self
+    }
+
+    /// Find the route handlers of the web frameworks that lack the function attribute,
+    /// listed in [`Discovery::undocumented`]
+    ///
+    /// Handlers marked with `#[utoipa_undocumented]` are allowed to stay undocumented
+    pub fn coverage(mut self) -> Self {
+        self.coverage = true;
+        self
+    }
+
+    /// Every item found in the sources, including the ones left out of the documentation
+    pub fn discover(&self) -> syn::Result<Vec<DiscoveredItem>> {
+        Ok(self.explore()?.items)
+    }
+
+    /// Like [`Discoverer::discover`], with the explanation of the item given to [`Discoverer::explain`]
+    ///
+    /// Every source is explored, so that all the errors can be reported at once
+    pub fn explore(&self) -> syn::Result<Discovery> {
+        let mut discovered = Discovery::default();
+        let mut errors: Option<syn::Error> = None;
+        for source in &self.sources {
+            match self.explore_source(source) {
+                Ok(discovery) => discovered.extend(discovery),
+                Err(e) => combine_error(&mut errors, e),
+            }
+        }
+        discovered
+            .items
+            .retain(|item| self.filters.iter().all(|filter| filter(item)));
+        if self.coverage {
+            discovered.undocumented = undocumented(&discovered.functions, &discovered.registrations);
+        }
+        discovered.sort_modules();
+        crate::nest::exclude_nested_handlers(&mut discovered.items);
+
+        errors.map_or(Ok(discovered), Err)
+    }
+
+    /// Discover everything from a file, will explore folder recursively
+    ///
+    /// Errors are reported at the span of the source, which should point at the path literal given by the user
+    fn explore_source(&self, source: &Source) -> syn::Result<Discovery> {
+        let error = |e: &dyn fmt::Display| {
+            syn::Error::new(
+                source.span,
+                format!("utoipauto: failed to discover `{}`: {}", source.path, e),
+            )
+        };
+        let files = match &source.file {
+            Some(file) => vec![SourceFile {
+                path: source.path.clone(),
+                content: String::new(),
+                file: file.clone(),
+            }],
+            None => read_files(&source.path).map_err(|e| error(&e))?,
+        };
+        let crate_name = &source.crate_name;
+        let modules = ModuleTree::new(&files, crate_name).map_err(|e| error(&e))?;
+
+        let mut discovered = Discovery::default();
+        let mut errors: Option<syn::Error> = None;
+        for file in &files {
+            let module_path = extract_module_name_from_path(&file.path, crate_name);
+            let module_name = path_to_string(&module_path);
+            let scope = Scope {
+                cfg: modules.cfg_of(&module_name),
+                unreachable: !modules.is_reachable(&module_name),
+                public: modules.is_public(&module_name),
+                path: module_path,
+            };
+            let mut walker = ModuleWalker {
+                params: self,
+                file: &file.path,
+                lines: LineIndex::new(&file.content),
+                discovery: Discovery::default(),
+            };
+            if self.coverage {
+                discovered
+                    .registrations
+                    .extend(registrations(&module_name, &file.path, &file.file));
+            }
+            walker.discovery.module_files.push(DiscoveredModule {
+                path: scope.path.clone(),
+                doc: doc_comment(&file.file.attrs),
+            });
+            match walker.parse_module_items(&scope, &file.file.items) {
+                Ok(()) => discovered.extend(walker.discovery),
+                Err(e) => combine_error(
+                    &mut errors,
+                    syn::Error::new(source.span, format!("utoipauto: error in `{}`: {}", file.path, e)),
+                ),
+            }
+        }
+
+        // The cfg predicates of another crate can't be evaluated from the crate using the macro
+        if crate_name != "crate" && !crate_name.starts_with("crate::") {
+            discovered.items.iter_mut().for_each(|item| item.cfg = None);
+            discovered.functions.iter_mut().for_each(|function| function.cfg = None);
+        }
+        discovered.positions = modules.positions;
+
+        errors.map_or(Ok(discovered), Err)
+    }
+}
+
+/// The module being explored
+struct Scope {
+    path: syn::Path,
+    /// `#[cfg(...)]` predicates of the module and its parents
+    cfg: Vec<TokenStream>,
+    unreachable: bool,
+    /// The module and its parents are declared `pub`
+    public: bool,
+}
+
+impl Scope {
+    fn child(&self, module: &ItemMod) -> syn::Result<Scope> {
+        let mut cfg = self.cfg.clone();
+        cfg.extend(cfg_predicates(&module.attrs)?);
+        Ok(Scope {
+            path: build_path(&self.path, &module.ident),
+            cfg,
+            unreachable: self.unreachable,
+            public: self.public && matches!(module.vis, Visibility::Public(_)),
         })
+    }
+}
+
+/// The modules declared by the explored files, used to find which files are actually compiled
+struct ModuleTree {
+    /// Module path => `mod` declarations of the module
+    declarations: HashMap<String, Vec<ModuleDeclaration>>,
+    /// Module path => `#[cfg(...)]` predicates of the module itself
+    cfg: HashMap<String, Vec<TokenStream>>,
+    /// Module path => position of its `mod` declaration among the ones of its parent
+    positions: HashMap<String, usize>,
+    /// The modules whose `mod` declaration isn't `pub`
+    private: HashSet<String>,
 }
 
-/// Search for ToSchema and ToResponse implementations in attr
-fn parse_from_attr(
-    a: &Vec<Attribute>,
-    name: syn::Path,
-    generic_params: Punctuated<GenericParam, Comma>,
-    params: &Parameters,
-) -> Vec<DiscoverType> {
-    let mut out: Vec<DiscoverType> = vec![];
-    if !generic_params.iter().all(|p| matches!(p, GenericParam::Lifetime(_))) {
-        return out;
-    }
-
-    for attr in a {
-        let meta = &attr.meta;
-        if meta.path().is_ident("utoipa_ignore") {
-            return vec![];
-        }
-        if meta.path().is_ident("derive") {
-            let nested = attr
-                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
-                .expect("Failed to parse derive attribute");
-            for nested_meta in nested {
-                if nested_meta.path().segments.len() == 2 && nested_meta.path().segments[0].ident == "utoipa" {
-                    match nested_meta.path().segments[1].ident.to_string().as_str() {
-                        "ToSchema" => out.push(DiscoverType::Model(name.clone())),
-                        "ToResponse" => out.push(DiscoverType::Response(name.clone())),
-                        _ => {}
+struct ModuleDeclaration {
+    name: String,
+    /// Declared with `#[path = "..."]`, so we can't know which file it points to
+    has_path: bool,
+}
+
+impl ModuleTree {
+    fn new(files: &[SourceFile], crate_name: &str) -> syn::Result<Self> {
+        let mut tree = ModuleTree {
+            declarations: HashMap::new(),
+            cfg: HashMap::new(),
+            positions: HashMap::new(),
+            private: HashSet::new(),
+        };
+        for file in files {
+            let module_path = path_to_string(&extract_module_name_from_path(&file.path, crate_name));
+            tree.cfg
+                .entry(module_path.clone())
+                .or_default()
+                .extend(cfg_predicates(&file.file.attrs)?);
+            tree.add_declarations(&module_path, &file.file.items)?;
+        }
+        Ok(tree)
+    }
+
+    fn add_declarations(&mut self, module_path: &str, items: &[Item]) -> syn::Result<()> {
+        self.declarations.entry(module_path.to_string()).or_default();
+        let modules = items.iter().filter_map(|item| match item {
+            Item::Mod(m) => Some(m),
+            _ => None,
+        });
+        for (position, m) in modules.enumerate() {
+            let child_path = format!("{}::{}", module_path, m.ident);
+            self.positions.insert(child_path.clone(), position);
+            if !matches!(m.vis, Visibility::Public(_)) {
+                self.private.insert(child_path.clone());
+            }
+            self.cfg
+                .entry(child_path.clone())
+                .or_default()
+                .extend(cfg_predicates(&m.attrs)?);
+            match &m.content {
+                Some((_, items)) => self.add_declarations(&child_path, items)?,
+                None => self
+                    .declarations
+                    .entry(module_path.to_string())
+                    .or_default()
+                    .push(ModuleDeclaration {
+                        name: m.ident.to_string(),
+                        has_path: m.attrs.iter().any(|attr| attr.path().is_ident("path")),
+                    }),
+            }
+        }
+        Ok(())
+    }
+
+    /// A module is unreachable if its parent was explored but doesn't declare it
+    fn is_reachable(&self, module_path: &str) -> bool {
+        let Some((parent, name)) = module_path.rsplit_once("::") else {
+            return true;
+        };
+        let declared = match self.declarations.get(parent) {
+            // The parent wasn't explored, we can't tell
+            None => true,
+            Some(declarations) => declarations.iter().any(|d| d.has_path || d.name == name),
+        };
+        declared && self.is_reachable(parent)
+    }
+
+    /// A module is public if none of its parents, nor itself, is declared without `pub`
+    ///
+    /// The modules whose declaration wasn't explored are assumed public
+    fn is_public(&self, module_path: &str) -> bool {
+        match module_path.rsplit_once("::") {
+            Some((parent, _)) => !self.private.contains(module_path) && self.is_public(parent),
+            None => true,
+        }
+    }
+
+    /// The `#[cfg(...)]` predicates of a module and of all its parents
+    fn cfg_of(&self, module_path: &str) -> Vec<TokenStream> {
+        let mut prefix = String::new();
+        let mut predicates = vec![];
+        for segment in module_path.split("::") {
+            if !prefix.is_empty() {
+                prefix.push_str("::");
+            }
+            prefix.push_str(segment);
+            if let Some(cfg) = self.cfg.get(&prefix) {
+                predicates.extend(cfg.iter().cloned());
+            }
+        }
+        predicates
+    }
+}
+
+/// Explore the items of a file
+struct ModuleWalker<'a> {
+    params: &'a Discoverer,
+    file: &'a str,
+    lines: LineIndex,
+    discovery: Discovery,
+}
+
+impl ModuleWalker<'_> {
+    fn parse_module_items(&mut self, scope: &Scope, items: &[Item]) -> syn::Result<()> {
+        let imports = Imports::new(&scope.path, items);
+        for item in items {
+            match item {
+                Item::Mod(m) => {
+                    self.lines.find_next("mod", &m.ident.to_string());
+                    let child = scope.child(m)?;
+                    // Also holds the `//!` docs of an inline module
+                    self.push_module(&child.path, &m.attrs);
+                    if let Some((_, items)) = &m.content {
+                        self.parse_module_items(&child, items)?;
                     }
-                } else {
-                    if nested_meta.path().is_ident(&params.schema_attribute_name) {
-                        out.push(DiscoverType::Model(name.clone()));
+                }
+                Item::Fn(f) => {
+                    let line = self.lines.find_next("fn", &f.sig.ident.to_string());
+                    let path = build_path(&scope.path, &f.sig.ident);
+                    let fn_attribute_name = &self.params.utoipa_rule.fn_attribute_name;
+                    let found = Found::new(path, line, &f.vis, f.sig.ident.span(), &f.attrs)
+                        .with_route(Route::from_attributes(&f.attrs, fn_attribute_name))
+                        .with_references(handler_references(&f.attrs, fn_attribute_name, &imports));
+                    let classified = self.classify(scope, RuleItem::Fn(f), "function", &found, None)?;
+                    if self.params.coverage {
+                        self.push_function(scope, &found, classified)?;
                     }
-                    if nested_meta.path().is_ident(&params.response_attribute_name) {
-                        out.push(DiscoverType::Response(name.clone()));
+                }
+                Item::Struct(s) => {
+                    let line = self.lines.find_next("struct", &s.ident.to_string());
+                    let path = build_path(&scope.path, &s.ident);
+                    let found = Found::new(path, line, &s.vis, s.ident.span(), &s.attrs)
+                        .with_references(field_references(&s.fields, &imports));
+                    self.classify(scope, RuleItem::Struct(s), "struct", &found, Some(&s.generics))?;
+                }
+                Item::Enum(e) => {
+                    let line = self.lines.find_next("enum", &e.ident.to_string());
+                    let path = build_path(&scope.path, &e.ident);
+                    let found = Found::new(path, line, &e.vis, e.ident.span(), &e.attrs).with_references(
+                        e.variants
+                            .iter()
+                            .flat_map(|variant| field_references(&variant.fields, &imports))
+                            .collect(),
+                    );
+                    self.classify(scope, RuleItem::Enum(e), "enum", &found, Some(&e.generics))?;
+                }
+                Item::Impl(im) => {
+                    // Only trait implementations of a named type can document it, `impl Display for &Foo` can't
+                    let Some((_, trait_path, _)) = &im.trait_ else { continue };
+                    let syn::Type::Path(self_ty) = im.self_ty.as_ref() else {
+                        continue;
+                    };
+                    if self_ty.qself.is_some() || !self.is_classified(&RuleItem::Impl(im))? {
+                        continue;
                     }
+                    let line = self_type_name(im).and_then(|name| self.lines.find_next("for", &name));
+                    let mut path = scope.path.clone();
+                    path.segments.extend(self_ty.path.segments.iter().cloned());
+                    let found = Found::new(path, line, &Visibility::Inherited, im.self_ty.span(), &im.attrs);
+                    let what = format!("`impl {}`", path_to_string(trait_path));
+                    self.classify(scope, RuleItem::Impl(im), &what, &found, Some(&im.generics))?;
                 }
+                _ => {}
             }
         }
+        Ok(())
     }
 
-    out
-}
+    /// Whether a discovery rule classifies the item, as ignored or as documented
+    fn is_classified(&self, item: &RuleItem<'_>) -> syn::Result<bool> {
+        for rule in self.params.all_rules() {
+            if !rule.classify(item)?.is_empty() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Ask every discovery rule what the item documents, and push it once for each kind
+    ///
+    /// Returns `true` if a rule classifies the item, as ignored or as documented
+    fn classify(
+        &mut self,
+        scope: &Scope,
+        item: RuleItem<'_>,
+        what: &str,
+        found: &Found,
+        generics: Option<&Generics>,
+    ) -> syn::Result<bool> {
+        let explained = match item {
+            // Impl blocks are explained with their type, but they are not suggested
+            RuleItem::Impl(_) => self.is_explained(&found.path),
+            _ => self.see(&found.path),
+        };
+        if explained {
+            self.explain_found(what, &found.visibility, found.line);
+        }
 
-fn parse_from_impl(im: &ItemImpl, module_base_path: &syn::Path, params: &Parameters) -> Vec<DiscoverType> {
-    im.trait_
-        .as_ref()
-        .and_then(|trt| trt.1.segments.last().map(|p| p.ident.to_string()))
-        .and_then(|impl_name| {
-            if impl_name.eq(params.schema_attribute_name.as_str()) {
-                Some(vec![DiscoverType::CustomModelImpl(build_path(
-                    module_base_path,
-                    &im.self_ty,
-                ))])
-            } else if impl_name.eq(params.response_attribute_name.as_str()) {
-                Some(vec![DiscoverType::CustomResponseImpl(build_path(
-                    module_base_path,
-                    &im.self_ty,
-                ))])
-            } else {
-                None
+        let mut kinds: Vec<DiscoveredKind> = vec![];
+        let mut ignored = false;
+        for rule in self.params.all_rules() {
+            let classifications = rule.classify(&item)?;
+            if explained && !classifications.is_empty() {
+                let list = classifications.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+                self.explain(format!(
+                    "the rule `{}` classifies it as {}",
+                    rule.name(),
+                    list.join(" and ")
+                ));
             }
-        })
-        .unwrap_or_default()
-}
+            for classification in classifications {
+                let kind = match classification {
+                    Classification::Path => DiscoveredKind::Path,
+                    Classification::Schema => DiscoveredKind::Schema,
+                    Classification::Response => DiscoveredKind::Response,
+                    Classification::Modifier => DiscoveredKind::Modifier,
+                    Classification::Nest => DiscoveredKind::Nest,
+                    Classification::Ignore => {
+                        ignored = true;
+                        continue;
+                    }
+                };
+                if !kinds.contains(&kind) {
+                    kinds.push(kind);
+                }
+            }
+        }
+        if explained && kinds.is_empty() {
+            self.explain(format!(
+                "no discovery rule classifies it as a path, a schema or a response, found: {}",
+                attribute_list(item.attrs())
+            ));
+        }
 
-fn parse_function(f: &ItemFn, fn_attributes_name: &str) -> Vec<Ident> {
-    let mut fns_name: Vec<Ident> = vec![];
-    if should_parse_fn(f) {
-        for i in 0..f.attrs.len() {
-            if f.attrs[i]
-                .meta
-                .path()
-                .segments
-                .iter()
-                .any(|item| item.ident.eq(fn_attributes_name))
-            {
-                fns_name.push(f.sig.ident.clone());
+        let skip_reason = if ignored {
+            Some(SkipReason::Ignored)
+        } else if generics.is_some_and(|g| !g.params.iter().all(|p| matches!(p, GenericParam::Lifetime(_)))) {
+            Some(SkipReason::Generic)
+        } else {
+            None
+        };
+        let classified = ignored || !kinds.is_empty();
+        for kind in kinds {
+            self.push(scope, kind, found, skip_reason.clone())?;
+        }
+        Ok(classified)
+    }
+
+    /// Remember a module declared with `mod`, with the doc comments among `attrs`
+    fn push_module(&mut self, path: &syn::Path, attrs: &[Attribute]) {
+        self.discovery.modules.push(DiscoveredModule {
+            path: path.clone(),
+            doc: doc_comment(attrs),
+        });
+    }
+
+    /// Remember a function, to check if it is an undocumented route handler
+    fn push_function(&mut self, scope: &Scope, found: &Found, classified: bool) -> syn::Result<()> {
+        let mut cfg = scope.cfg.clone();
+        cfg.extend(cfg_predicates(&found.attrs)?);
+        self.discovery.functions.push(Function {
+            path: found.path.clone(),
+            file: self.file.to_string(),
+            line: found.line.unwrap_or_default(),
+            span: found.span,
+            cfg: combine_predicates(cfg),
+            route: found.route.clone(),
+            classified,
+            allowed: is_allowed(&found.attrs),
+        });
+        Ok(())
+    }
+
+    fn push(
+        &mut self,
+        scope: &Scope,
+        kind: DiscoveredKind,
+        found: &Found,
+        skip_reason: Option<SkipReason>,
+    ) -> syn::Result<()> {
+        let mut cfg = scope.cfg.clone();
+        cfg.extend(cfg_predicates(&found.attrs)?);
+        let skip_reason = match skip_reason {
+            Some(SkipReason::Ignored) => Some(SkipReason::Ignored),
+            _ if scope.unreachable => Some(SkipReason::Unreachable),
+            skip_reason => skip_reason,
+        };
+        let cfg = combine_predicates(cfg);
+        let order = match kind {
+            DiscoveredKind::Modifier => crate::modifiers::modifier_order(&found.attrs)?,
+            _ => None,
+        };
+        let nest = match kind {
+            DiscoveredKind::Nest => match crate::nest::nest_path(&found.path, &found.attrs)? {
+                Some(path) => Some(path),
+                None => {
+                    if self.is_explained(&found.path) {
+                        self.explain(
+                            "it is not nested, it is at the crate root without `#[utoipauto::nest(path = \"...\")]`"
+                                .to_string(),
+                        );
+                    }
+                    return Ok(());
+                }
+            },
+            _ => None,
+        };
+        if self.is_explained(&found.path) {
+            if let Some(cfg) = &cfg {
+                self.explain(format!("it is only compiled with `cfg({})`", cfg));
             }
+            self.explain(match &skip_reason {
+                Some(reason) => format!("the {} is skipped because {}", kind, reason),
+                None => format!("the {} is documented", kind),
+            });
+        }
+        self.discovery.items.push(DiscoveredItem {
+            kind,
+            path: found.path.clone(),
+            visibility: found.visibility.clone(),
+            file: self.file.to_string(),
+            line: found.line.unwrap_or_default(),
+            span: found.span,
+            attrs: found.attrs.clone(),
+            route: found.route.clone(),
+            cfg,
+            skip_reason,
+            order,
+            nest,
+            references: found.references.clone(),
+            public_module: scope.public,
+        });
+        Ok(())
+    }
+
+    /// `true` if the item is the one given to `explain = "..."`
+    fn is_explained(&self, path: &syn::Path) -> bool {
+        self.params
+            .explain
+            .as_ref()
+            .is_some_and(|target| *target == path_without_generics(path))
+    }
+
+    /// Remember the item to suggest it if the explained item isn't found, and check if it is the explained one
+    fn see(&mut self, path: &syn::Path) -> bool {
+        if self.params.explain.is_some() {
+            self.discovery.seen.push(path_without_generics(path));
+        }
+        self.is_explained(path)
+    }
+
+    fn explain(&mut self, decision: String) {
+        self.discovery.explanation.push(decision);
+    }
+
+    fn explain_found(&mut self, what: &str, vis: &Visibility, line: Option<usize>) {
+        let visibility = match vis {
+            Visibility::Public(_) => "public",
+            Visibility::Restricted(_) => "restricted",
+            Visibility::Inherited => "private",
+        };
+        self.explain(format!("found a {} {} at {}", visibility, what, self.location(line)));
+    }
+
+    fn location(&self, line: Option<usize>) -> String {
+        match line {
+            Some(line) => format!("{}:{}", self.file, line),
+            None => self.file.to_string(),
         }
     }
-    fns_name
 }
 
-fn should_parse_fn(f: &ItemFn) -> bool {
-    !f.attrs.is_empty() && !is_ignored(f)
+/// What is known about a discovered item, whatever its kind
+struct Found {
+    path: syn::Path,
+    line: Option<usize>,
+    visibility: Visibility,
+    span: Span,
+    attrs: Vec<Attribute>,
+    route: Option<Route>,
+    references: Vec<TypeReference>,
 }
 
-fn is_ignored(f: &ItemFn) -> bool {
-    f.attrs.iter().any(|attr| {
-        if let Some(name) = attr.path().get_ident() {
-            name.eq("utoipa_ignore")
-        } else {
-            false
+impl Found {
+    fn new(path: syn::Path, line: Option<usize>, visibility: &Visibility, span: Span, attrs: &[Attribute]) -> Self {
+        Self {
+            path,
+            line,
+            visibility: visibility.clone(),
+            span,
+            attrs: attrs.to_vec(),
+            route: None,
+            references: vec![],
         }
-    })
+    }
+
+    fn with_route(mut self, route: Option<Route>) -> Self {
+        self.route = route;
+        self
+    }
+
+    fn with_references(mut self, references: Vec<TypeReference>) -> Self {
+        self.references = references;
+        self
+    }
+}
+
+/// `#[derive(Debug, Serialize)] #[serde]`, to explain why no attribute matched
+fn attribute_list(attrs: &[Attribute]) -> String {
+    if attrs.is_empty() {
+        return "no attributes".to_string();
+    }
+    attrs
+        .iter()
+        .map(|attr| match &attr.meta {
+            Meta::List(list) if list.path.is_ident("derive") => {
+                format!("#[derive({})]", list.tokens.to_string().replace(" ,", ","))
+            }
+            meta => format!("#[{}]", path_to_string(meta.path())),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `crate::Model` for `crate::Model<'a>`
+pub(crate) fn path_without_generics(path: &syn::Path) -> String {
+    let path = path_to_string(path);
+    match path.split_once('<') {
+        Some((path, _)) => path.to_string(),
+        None => path,
+    }
+}
+
+/// The name of the implementing type: `Model` for `impl ToSchema for Model`
+fn self_type_name(im: &ItemImpl) -> Option<String> {
+    match im.self_ty.as_ref() {
+        syn::Type::Path(type_path) => type_path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// The predicates of the `#[cfg(...)]` attributes
+fn cfg_predicates(attrs: &[Attribute]) -> syn::Result<Vec<TokenStream>> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .map(|attr| Ok(attr.meta.require_list()?.tokens.clone()))
+        .collect()
+}
+
+/// The text of the `#[doc = "..."]` attributes, `None` if there is none
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(doc),
+                        ..
+                    }),
+                ..
+            }) => Some(doc.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.lines()
+                .map(|line| line.strip_prefix(' ').unwrap_or(line).to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// Combine several cfg predicates into one: `all(a, b)`
+fn combine_predicates(mut predicates: Vec<TokenStream>) -> Option<TokenStream> {
+    match predicates.len() {
+        0 => None,
+        1 => predicates.pop(),
+        _ => Some(quote!(all(#(#predicates),*))),
+    }
+}
+
+fn path_to_string(path: &syn::Path) -> String {
+    path.to_token_stream().to_string().replace(' ', "")
 }
 
 fn build_path(file_path: &syn::Path, fn_name: impl ToTokens) -> syn::Path {
@@ -184,26 +1159,280 @@ fn build_path(file_path: &syn::Path, fn_name: impl ToTokens) -> syn::Path {
 #[cfg(test)]
 mod test {
     use quote::quote;
-    use syn::ItemFn;
+
+    use super::{DiscoveredItem, DiscoveredKind, Discoverer, Discovery, ModuleTree, ModuleWalker, Scope, SkipReason};
+    use crate::file_utils::{LineIndex, SourceFile};
+    use crate::rules::{Classification, MarkerRule};
+
+    fn walk(content: &str) -> Vec<DiscoveredItem> {
+        walk_with(content, Discoverer::new()).items
+    }
+
+    fn walk_with(content: &str, params: Discoverer) -> Discovery {
+        let mut walker = ModuleWalker {
+            params: &params,
+            file: "./src/lib.rs",
+            lines: LineIndex::new(content),
+            discovery: Default::default(),
+        };
+        let scope = Scope {
+            path: syn::parse_quote!(crate),
+            cfg: vec![],
+            unreachable: false,
+            public: true,
+        };
+        walker
+            .parse_module_items(&scope, &syn::parse_file(content).unwrap().items)
+            .unwrap();
+        walker.discovery
+    }
 
     #[test]
-    fn test_parse_function() {
-        let quoted = quote! {
-            #[utoipa]
-            pub fn route_custom() {}
+    fn test_skipped_items() {
+        let items = walk(
+            r#"
+#[derive(utoipa::ToSchema)]
+pub struct Response<T> {
+    data: T,
+}
+
+#[utoipa_ignore]
+#[utoipa::path(get, path = "/ignored")]
+pub fn ignored() {}
+
+#[utoipa::path(get, path = "/route")]
+pub fn route() {}
+"#,
+        );
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].path_string(), "crate::Response");
+        assert_eq!(items[0].kind, DiscoveredKind::Schema);
+        assert_eq!(items[0].skip_reason, Some(SkipReason::Generic));
+        assert_eq!(items[0].line, 3);
+        assert_eq!(items[1].path_string(), "crate::ignored");
+        assert_eq!(items[1].skip_reason, Some(SkipReason::Ignored));
+        assert_eq!(items[1].line, 9);
+        assert_eq!(items[2].path_string(), "crate::route");
+        assert_eq!(items[2].skip_reason, None);
+        assert_eq!(items[2].line, 12);
+    }
+
+    #[test]
+    fn test_impl_self_types() {
+        let items = walk(
+            r#"
+impl std::fmt::Display for &Model {}
+
+impl Clone for (u8, u8) {}
+
+impl Default for [Model] {}
+
+impl utoipa::ToSchema for ::models::Model {}
+"#,
+        );
+        let paths = items.iter().map(|item| item.path_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["crate::models::Model"]);
+    }
+
+    #[test]
+    fn test_cfg_items() {
+        let items = walk(
+            r#"
+#[cfg(feature = "admin")]
+mod admin {
+    #[cfg(unix)]
+    #[utoipa::path(get, path = "/admin")]
+    pub fn admin() {}
+}
+"#,
+        );
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].path_string(), "crate::admin::admin");
+        assert_eq!(
+            items[0].cfg.as_ref().unwrap().to_string(),
+            quote!(all(feature = "admin", unix)).to_string()
+        );
+        assert_eq!(items[0].skip_reason, None);
+    }
+
+    #[test]
+    fn test_cfg_groups() {
+        let items = walk(
+            r#"
+#[cfg(feature = "admin")]
+#[utoipa::path(get, path = "/admin")]
+pub fn admin() {}
+
+#[utoipa::path(get, path = "/always")]
+pub fn always() {}
+
+#[cfg(unix)]
+#[utoipa::path(get, path = "/unix")]
+pub fn unix() {}
+
+#[cfg(feature = "admin")]
+#[utoipa::path(get, path = "/admin/users")]
+pub fn admin_users() {}
+"#,
+        );
+        let groups = super::cfg_groups(&items);
+        let groups = groups
+            .iter()
+            .map(|(cfg, items)| {
+                (
+                    cfg.as_ref().map(ToString::to_string),
+                    items.iter().map(|item| item.path_string()).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            vec![
+                (None, vec!["crate::always".to_string()]),
+                (
+                    Some(quote!(feature = "admin").to_string()),
+                    vec!["crate::admin".to_string(), "crate::admin_users".to_string()]
+                ),
+                (Some(quote!(unix).to_string()), vec!["crate::unix".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let content = r#"
+#[derive(Debug)]
+pub struct Model;
+
+#[get("/users")]
+fn get_users() {}
+"#;
+        let discovery = walk_with(content, Discoverer::new().explain("crate::get_users"));
+        assert_eq!(
+            discovery.explanation,
+            vec![
+                "found a private function at ./src/lib.rs:6",
+                "no discovery rule classifies it as a path, a schema or a response, found: #[get]",
+            ]
+        );
+        assert_eq!(discovery.seen, vec!["crate::Model", "crate::get_users"]);
+    }
+
+    #[test]
+    fn test_discoverer_root() {
+        let items = Discoverer::new()
+            .root("../utoipauto/tests/default_features/controllers")
+            .filter(|item| item.is_discovered())
+            .discover()
+            .unwrap();
+        let routes: Vec<_> = items
+            .iter()
+            .map(|item| (item.path_string(), item.route.clone().unwrap().path.unwrap()))
+            .collect();
+        assert_eq!(
+            routes,
+            vec![
+                (
+                    "crate::default_features::controllers::controller1::route1".to_string(),
+                    "/route1".to_string()
+                ),
+                (
+                    "crate::default_features::controllers::controller2::route3".to_string(),
+                    "/route3".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_docs() {
+        let users: syn::File = syn::parse_quote! {
+            //! Manage the accounts
         };
+        let lib: syn::File = syn::parse_quote! {
+            /// The users
+            mod users;
 
-        let item_fn: ItemFn = syn::parse2(quoted).unwrap();
-        let fn_name = super::parse_function(&item_fn, "utoipa");
-        assert_eq!(fn_name, vec!["route_custom"]);
+            mod billing {
+                //! Invoices and payments
+            }
+        };
+        let discovery = Discoverer::new()
+            .file("./src/users.rs", users)
+            .file("./src/lib.rs", lib)
+            .explore()
+            .unwrap();
+        let modules = discovery
+            .modules
+            .iter()
+            .map(|module| (super::path_to_string(&module.path), module.doc.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            modules,
+            vec![
+                ("crate".to_string(), None),
+                ("crate::users".to_string(), Some("The users\n\nManage the accounts")),
+                ("crate::billing".to_string(), Some("Invoices and payments")),
+            ]
+        );
+    }
 
-        let quoted = quote! {
-            #[handler]
-            pub fn route_custom() {}
+    #[test]
+    fn test_module_tree_is_public() {
+        let source = |path: &str, file: syn::File| SourceFile {
+            path: path.to_string(),
+            content: String::new(),
+            file,
         };
+        let files = [
+            source(
+                "./src/lib.rs",
+                syn::parse_quote! {
+                    pub mod api;
+                    mod internal;
+                },
+            ),
+            source(
+                "./src/api/mod.rs",
+                syn::parse_quote! {
+                    pub mod users;
+                    pub(crate) mod admin;
+                },
+            ),
+        ];
+        let tree = ModuleTree::new(&files, "crate").unwrap();
+        assert!(tree.is_public("crate"));
+        assert!(tree.is_public("crate::api::users"));
+        assert!(!tree.is_public("crate::api::admin"));
+        assert!(!tree.is_public("crate::internal"));
+        assert!(!tree.is_public("crate::internal::models"));
+    }
+
+    #[test]
+    fn test_discoverer_missing_root() {
+        assert!(Discoverer::new().root("./does/not/exist").discover().is_err());
+    }
+
+    #[test]
+    fn test_discoverer_rules() {
+        let file: syn::File = syn::parse_quote! {
+            #[derive(ApiModel)]
+            pub struct User;
 
-        let item_fn: ItemFn = syn::parse2(quoted).unwrap();
-        let fn_name = super::parse_function(&item_fn, "handler");
-        assert_eq!(fn_name, vec!["route_custom"]);
+            #[derive(ApiModel)]
+            #[internal]
+            pub struct Secret;
+        };
+        let items = Discoverer::new()
+            .file("./src/lib.rs", file)
+            .rule(MarkerRule::new(Classification::Schema, "ApiModel"))
+            .rule(MarkerRule::new(Classification::Ignore, "internal"))
+            .discover()
+            .unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].path_string(), "crate::User");
+        assert_eq!(items[0].kind, DiscoveredKind::Schema);
+        assert_eq!(items[0].skip_reason, None);
+        assert_eq!(items[1].skip_reason, Some(SkipReason::Ignored));
     }
 }
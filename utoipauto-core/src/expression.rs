@@ -0,0 +1,148 @@
+//! `discover_openapi!(...)`: the discovered items as a `utoipa::openapi::OpenApi` expression
+//!
+//! Unlike the attribute, the expression doesn't need a documentation struct, so it can be merged or nested into
+//! a documentation assembled at runtime.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::discover::{DiscoveredItem, DiscoveredKind};
+use crate::modifiers::ordered_modifiers;
+
+/// The parameters accepted by `discover_openapi!(...)`
+pub const EXPRESSION_PARAMETERS: &[&str] = &[
+    "paths",
+    "function_attribute_name",
+    "schema_attribute_name",
+    "response_attribute_name",
+    "rules",
+    "warnings",
+    "include",
+];
+
+/// A block building the documentation of the discovered items with `OpenApiBuilder`
+///
+/// Each item is added by a statement under its cfg predicate. A handler is merged as a documentation derived with
+/// `paths(handler)`, with the schemas it references. The discovered modifiers run last, in their order.
+pub fn openapi_expression(items: &[DiscoveredItem]) -> TokenStream {
+    let statements = items.iter().filter(|item| item.is_discovered()).map(|item| {
+        let cfg = item.cfg.as_ref().map(|cfg| quote!(#[cfg(#cfg)]));
+        let statement = match item.kind {
+            DiscoveredKind::Path => {
+                // The documentation of the handler alone holds the schemas it references
+                let handler = &item.path;
+                quote!({
+                    #[derive(::utoipa::OpenApi)]
+                    #[openapi(paths(#handler))]
+                    struct Handler;
+                    openapi.merge(<Handler as ::utoipa::OpenApi>::openapi());
+                })
+            }
+            DiscoveredKind::Schema => {
+                let schema = &item.path;
+                quote!({
+                    schemas.push((
+                        <#schema as ::utoipa::ToSchema>::name().to_string(),
+                        <#schema as ::utoipa::PartialSchema>::schema(),
+                    ));
+                    <#schema as ::utoipa::ToSchema>::schemas(&mut schemas);
+                })
+            }
+            DiscoveredKind::Response => {
+                let response = &item.path;
+                quote!({
+                    let (name, response) = <#response as ::utoipa::ToResponse>::response();
+                    responses.push((name.to_string(), response));
+                })
+            }
+            DiscoveredKind::Nest => {
+                let api = &item.path;
+                match &item.nest {
+                    Some(path) => quote!({
+                        openapi = openapi.nest(#path, <#api as ::utoipa::OpenApi>::openapi());
+                    }),
+                    None => TokenStream::new(),
+                }
+            }
+            // Applied once the documentation is built
+            DiscoveredKind::Modifier => TokenStream::new(),
+        };
+        quote!(#cfg #statement)
+    });
+    let modifiers = ordered_modifiers(items).into_iter().map(|(item, _)| {
+        let cfg = item.cfg.as_ref().map(|cfg| quote!(#[cfg(#cfg)]));
+        let modifier = &item.path;
+        quote!(#cfg <#modifier as ::utoipa::Modify>::modify(&#modifier, &mut openapi);)
+    });
+
+    quote!({
+        let mut openapi = ::utoipa::openapi::OpenApiBuilder::new()
+            .info(::utoipa::openapi::Info::new(
+                ::core::env!("CARGO_PKG_NAME"),
+                ::core::env!("CARGO_PKG_VERSION"),
+            ))
+            .build();
+        let mut schemas = ::std::vec::Vec::new();
+        let mut responses = ::std::vec::Vec::new();
+        #(#statements)*
+        if !schemas.is_empty() || !responses.is_empty() {
+            let components = openapi.components.get_or_insert_with(::utoipa::openapi::Components::new);
+            components.schemas.extend(schemas);
+            components.responses.extend(responses);
+        }
+        #(#modifiers)*
+        openapi
+    })
+}
+
+/// `crate::api::__path_get_user`, the struct generated by `#[utoipa::path]` for `crate::api::get_user`
+pub(crate) fn path_struct(path: &syn::Path) -> syn::Path {
+    let mut path = path.clone();
+    if let Some(last) = path.segments.last_mut() {
+        last.ident = format_ident!("__path_{}", last.ident);
+    }
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use super::openapi_expression;
+    use crate::discover::Discoverer;
+
+    #[test]
+    fn test_openapi_expression() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/users")]
+            pub fn list_users() {}
+
+            #[cfg(feature = "admin")]
+            #[utoipa::path(delete, path = "/users/{id}")]
+            pub fn delete_user() {}
+
+            #[derive(ToSchema)]
+            pub struct User;
+
+            #[derive(ToSchema)]
+            pub struct Page<T>(T);
+
+            #[utoipauto::modifier]
+            pub struct SecurityAddon;
+
+            impl utoipa::Modify for SecurityAddon {}
+        };
+        let items = Discoverer::new().file("./src/api.rs", file).discover().unwrap();
+        let expression = openapi_expression(&items).to_string();
+        assert!(expression.contains(
+            "< crate :: api :: SecurityAddon as :: utoipa :: Modify > :: modify (& crate :: api :: SecurityAddon , & mut openapi) ; openapi"
+        ));
+        assert!(expression.contains(
+            "{ # [derive (:: utoipa :: OpenApi)] # [openapi (paths (crate :: api :: list_users))] struct Handler ; \
+             openapi . merge (< Handler as :: utoipa :: OpenApi > :: openapi ()) ; }"
+        ));
+        assert!(expression.contains(
+            "# [cfg (feature = \"admin\")] { # [derive (:: utoipa :: OpenApi)] # [openapi (paths (crate :: api :: delete_user))]"
+        ));
+        assert!(expression.contains("< crate :: api :: User as :: utoipa :: ToSchema > :: name ()"));
+        assert!(!expression.contains("Page"));
+    }
+}
@@ -7,38 +7,79 @@ use std::{
 
 use proc_macro2::Span;
 
+/// A parsed rust file, along with its path and its content
+pub struct SourceFile {
+    pub path: String,
+    pub content: String,
+    pub file: syn::File,
+}
+
 pub fn parse_file<T: Into<PathBuf>>(filepath: T) -> Result<syn::File, io::Error> {
+    read_file(filepath).map(|source| source.file)
+}
+
+/// Read and parse a rust file, keeping its content to locate the items
+pub fn read_file<T: Into<PathBuf>>(filepath: T) -> Result<SourceFile, io::Error> {
     let pb: PathBuf = filepath.into();
 
     if !pb.is_file() {
-        panic!("File not found: {:?}", pb);
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("File not found: {}", pb.display()),
+        ));
     }
 
     let mut file = File::open(&pb)?;
     let mut content = String::new();
     file.read_to_string(&mut content)?;
 
-    Ok(syn::parse_file(&content).unwrap_or_else(move |_| panic!("Failed to parse file {:?}", pb)))
+    let file = syn::parse_file(&content).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to parse file {}: {}", pb.display(), e),
+        )
+    })?;
+    Ok(SourceFile {
+        path: pb.to_string_lossy().to_string(),
+        content,
+        file,
+    })
 }
 
 /// Parse all the files in the given path
 pub fn parse_files<T: Into<PathBuf>>(path: T) -> Result<Vec<(String, syn::File)>, io::Error> {
-    let mut files: Vec<(String, syn::File)> = vec![];
+    Ok(read_files(path)?
+        .into_iter()
+        .map(|source| (source.path, source.file))
+        .collect())
+}
+
+/// Read and parse all the files in the given path, exploring folders recursively in alphabetical order
+pub fn read_files<T: Into<PathBuf>>(path: T) -> Result<Vec<SourceFile>, io::Error> {
+    let mut files: Vec<SourceFile> = vec![];
 
     let pb: PathBuf = path.into();
+    if !pb.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No such file or directory: {}", pb.display()),
+        ));
+    }
     if pb.is_file() {
         // we only parse rust files
         if is_rust_file(&pb) {
-            files.push((pb.to_str().unwrap().to_string(), parse_file(pb)?));
+            files.push(read_file(pb)?);
         }
     } else {
-        for entry in fs::read_dir(pb)? {
-            let entry = entry?;
-            let path = entry.path();
+        let mut entries = fs::read_dir(pb)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+        for path in entries {
             if path.is_file() && is_rust_file(&path) {
-                files.push((path.to_str().unwrap().to_string(), parse_file(path)?));
+                files.push(read_file(path)?);
             } else {
-                files.append(&mut parse_files(path)?);
+                files.append(&mut read_files(path)?);
             }
         }
     }
@@ -56,6 +97,139 @@ fn is_rust_file(path: &Path) -> bool {
         }
 }
 
+/// The words (identifiers and keywords) of a rust file with their line, comments and literals excluded
+///
+/// Files parsed from a proc macro don't carry line information in their spans,
+/// so the items are located by looking for their declaration (`fn name`, `struct Name`, ...),
+/// in the order in which they appear in the file.
+///
+/// # Example
+/// ```
+/// use utoipauto_core::file_utils::LineIndex;
+/// let mut index = LineIndex::new("// fn route()\nfn route() {}\n\nstruct Model;");
+/// assert_eq!(index.find_next("fn", "route"), Some(2));
+/// assert_eq!(index.find_next("struct", "Model"), Some(4));
+/// ```
+pub struct LineIndex {
+    words: Vec<(String, usize)>,
+    cursor: usize,
+}
+
+impl LineIndex {
+    pub fn new(content: &str) -> Self {
+        let chars: Vec<char> = content.chars().collect();
+        let mut words = Vec::new();
+        let mut line = 1;
+        let mut i = 0;
+
+        // Skip until `end` is found, counting the lines on the way
+        let skip_until = |i: &mut usize, line: &mut usize, end: &[char], escapes: bool| {
+            while *i < chars.len() && !chars[*i..].starts_with(end) {
+                if chars[*i] == '\n' {
+                    *line += 1;
+                }
+                // Escaped characters can't end a literal
+                if escapes && chars[*i] == '\\' {
+                    *i += 1;
+                }
+                *i += 1;
+            }
+            *i += end.len();
+        };
+
+        while i < chars.len() {
+            let c = chars[i];
+            if c == '\n' {
+                line += 1;
+                i += 1;
+            } else if chars[i..].starts_with(&['/', '/']) {
+                skip_until(&mut i, &mut line, &['\n'], false);
+                line += 1;
+            } else if chars[i..].starts_with(&['/', '*']) {
+                // Block comments can be nested
+                let mut depth = 0;
+                while i < chars.len() {
+                    if chars[i..].starts_with(&['/', '*']) {
+                        depth += 1;
+                        i += 2;
+                    } else if chars[i..].starts_with(&['*', '/']) {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        if chars[i] == '\n' {
+                            line += 1;
+                        }
+                        i += 1;
+                    }
+                }
+            } else if c == '"' {
+                i += 1;
+                skip_until(&mut i, &mut line, &['"'], true);
+            } else if c == '\'' {
+                if chars.get(i + 1) == Some(&'\\') || chars.get(i + 2) == Some(&'\'') {
+                    // Character literal
+                    i += 1;
+                    skip_until(&mut i, &mut line, &['\''], true);
+                } else {
+                    // Lifetime, its name isn't a declaration
+                    i += 1;
+                    while i < chars.len() && is_ident_char(chars[i]) {
+                        i += 1;
+                    }
+                }
+            } else if is_ident_char(c) {
+                let start = i;
+                while i < chars.len() && is_ident_char(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let raw_prefix = matches!(word.as_str(), "r" | "br" | "cr");
+                if matches!(word.as_str(), "b" | "c" | "r" | "br" | "cr") && chars.get(i) == Some(&'"') {
+                    // (Byte, C) string literal, escapes are only allowed in non raw strings
+                    i += 1;
+                    skip_until(&mut i, &mut line, &['"'], !raw_prefix);
+                } else if raw_prefix && chars.get(i) == Some(&'#') {
+                    let hashes = chars[i..].iter().take_while(|c| **c == '#').count();
+                    if chars.get(i + hashes) == Some(&'"') {
+                        // Raw string literal: r#"..."#
+                        i += hashes + 1;
+                        let end: Vec<char> = iter::once('"').chain(iter::repeat('#').take(hashes)).collect();
+                        skip_until(&mut i, &mut line, &end, false);
+                    } else {
+                        // Raw identifier: r#type
+                        i += 1;
+                    }
+                } else if !c.is_ascii_digit() {
+                    words.push((word, line));
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        Self { words, cursor: 0 }
+    }
+
+    /// Line of the next occurrence of the `first second` words, for example `fn route`
+    ///
+    /// The search starts after the previous match, and wraps around once if nothing is found.
+    pub fn find_next(&mut self, first: &str, second: &str) -> Option<usize> {
+        let second = second.trim_start_matches("r#");
+        let position = (self.cursor..self.words.len().saturating_sub(1))
+            .chain(0..self.cursor.min(self.words.len().saturating_sub(1)))
+            .find(|i| self.words[*i].0 == first && self.words[i + 1].0 == second)?;
+        self.cursor = position + 2;
+        Some(self.words[position].1)
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
 /// Extract the module name from the file path
 /// # Example
 /// ```
@@ -120,6 +294,12 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_parse_files_not_found() {
+        let error = parse_files("./does/not/exist").err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
     #[test]
     fn test_extract_module_name_from_path() {
         assert_eq!(
@@ -3,7 +3,23 @@ extern crate proc_macro2;
 extern crate quote;
 extern crate syn;
 pub mod attribute_utils;
+pub mod builder;
+pub mod cfg_items;
+pub mod coverage;
 pub mod discover;
+pub mod expression;
 pub mod file_utils;
+pub mod manifest;
+pub mod modifiers;
+mod modify;
+pub mod nest;
+pub mod operation_ids;
+pub mod prefixes;
+pub mod references;
+pub mod register;
+pub mod route_utils;
+pub mod router;
+pub mod rules;
 pub mod string_utils;
+pub mod tags;
 pub mod token_utils;
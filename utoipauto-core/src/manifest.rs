@@ -0,0 +1,345 @@
+//! Share the items discovered in a library crate with the crates documenting it
+//!
+//! `utoipauto::export_manifest!()` in the library defines a hidden `__utoipauto_manifest!` macro holding the
+//! discovered items, in the format of the build script registry. `#[utoipauto(import = [billing])]` calls the
+//! manifest of each imported crate in turn, each one passing the collected manifests to the next, and the last
+//! one to `utoipauto::__import!`, which adds the items with `billing::...` paths.
+//!
+//! The items behind a cfg predicate are held by a chain of `__utoipauto_manifest_cfg_<n>!` macros, one for each
+//! predicate, defined twice: with their registry when the predicate is enabled in the library, without otherwise.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    bracketed, parenthesized,
+    parse::{Parse, ParseStream},
+    Ident, LitStr, Token, Visibility,
+};
+
+use crate::builder::{parse_registry, render_registry};
+use crate::discover::{cfg_groups, DiscoveredItem, DiscoveredKind};
+use crate::token_utils::{check_parameters, Parameters};
+
+/// The parameters accepted by `export_manifest!(...)`, the others only make sense on a documentation struct
+const EXPORT_PARAMETERS: &[&str] = &[
+    "paths",
+    "function_attribute_name",
+    "schema_attribute_name",
+    "response_attribute_name",
+    "rules",
+    "warnings",
+];
+
+/// The name of the macro defined by `export_manifest!()` at the root of the library
+const MANIFEST_MACRO: &str = "__utoipauto_manifest";
+
+/// Check that `export_manifest!(...)` was only given exploration parameters
+pub fn check_export_parameters(params: &Parameters) -> syn::Result<()> {
+    check_parameters(params, EXPORT_PARAMETERS, "export_manifest!")
+}
+
+/// The `__utoipauto_manifest!` macro, followed by a `__utoipauto_manifest_cfg_<n>!` macro for each cfg predicate of
+/// the library
+pub fn export_manifest(items: &[DiscoveredItem]) -> TokenStream {
+    let exported = items.iter().filter(|item| is_exported(item));
+    let groups = cfg_groups(exported);
+    let manifest = format_ident!("{}", MANIFEST_MACRO);
+    let next = |i: usize| match i < groups.len() - 1 {
+        true => {
+            let next = format_ident!("{}_cfg_{}", MANIFEST_MACRO, i);
+            quote!($crate::#next)
+        }
+        false => quote!(::utoipauto::__import),
+    };
+    let mut code = TokenStream::new();
+    for (i, (cfg, group)) in groups.iter().enumerate() {
+        // The predicates are evaluated here, with the features of the library
+        let group = group
+            .iter()
+            .map(|item| DiscoveredItem {
+                cfg: None,
+                ..(*item).clone()
+            })
+            .collect::<Vec<_>>();
+        let registry = render_registry(&group);
+        let next = next(i);
+        match cfg {
+            None => code.extend(quote!(
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! #manifest {
+                    ([$($imported:tt)*] [$name:ident $(, $pending:ident)*] $($rest:tt)*) => {
+                        #next! { [$($imported)* $name #registry] [$($pending),*] $($rest)* }
+                    };
+                }
+            )),
+            Some(cfg) => {
+                let helper = format_ident!("{}_cfg_{}", MANIFEST_MACRO, i - 1);
+                code.extend(quote!(
+                    #[cfg(#cfg)]
+                    #[doc(hidden)]
+                    #[macro_export]
+                    macro_rules! #helper {
+                        ([$($imported:tt)*] $($rest:tt)*) => {
+                            #next! { [$($imported)* #registry] $($rest)* }
+                        };
+                    }
+
+                    #[cfg(not(#cfg))]
+                    #[doc(hidden)]
+                    #[macro_export]
+                    macro_rules! #helper {
+                        ([$($imported:tt)*] $($rest:tt)*) => {
+                            #next! { [$($imported)*] $($rest)* }
+                        };
+                    }
+                ));
+            }
+        }
+    }
+    code
+}
+
+/// Whether the item can be named from another crate, by its path
+///
+/// The items found through a trait implementation have no visibility of their own, their type is assumed public. A
+/// `pub` item of a module that isn't `pub`, or whose parent isn't, can't be named.
+fn is_exported(item: &DiscoveredItem) -> bool {
+    if !item.is_discovered() || !item.public_module {
+        return false;
+    }
+    match &item.visibility {
+        Visibility::Public(_) => true,
+        Visibility::Restricted(_) => false,
+        Visibility::Inherited => {
+            item.kind != DiscoveredKind::Path && !item.attrs.iter().any(|attr| attr.path().is_ident("derive"))
+        }
+    }
+}
+
+/// The call of the manifest of the next imported crate, or of `utoipauto::__import!` once they are all collected
+///
+/// `attributes` are the parameters of `#[utoipauto(...)]`, `item` the documentation struct
+pub fn import_call(
+    imported: &[(Ident, Vec<LitStr>)],
+    pending: &[Ident],
+    attributes: &TokenStream,
+    item: &TokenStream,
+) -> TokenStream {
+    let imported = imported.iter().map(|(name, registries)| quote!(#name #(#registries)*));
+    match pending.first() {
+        Some(next) => {
+            let manifest = Ident::new(MANIFEST_MACRO, next.span());
+            quote!(#next::#manifest! { [#(#imported)*] [#(#pending),*] (#attributes) #item })
+        }
+        None => quote!(::utoipauto::__import! { [#(#imported)*] [] (#attributes) #item }),
+    }
+}
+
+/// The input of `utoipauto::__import!`: `[billing "..." "..."] [shipping] (attributes) struct ApiDoc;`
+pub struct Import {
+    /// The crates whose manifest is collected, with their registries, one for the items compiled unconditionally and
+    /// one for each enabled cfg predicate
+    pub imported: Vec<(Ident, Vec<LitStr>)>,
+    /// The crates whose manifest is still to collect
+    pub pending: Vec<Ident>,
+    /// The parameters of `#[utoipauto(...)]`
+    pub attributes: TokenStream,
+    /// The documentation struct
+    pub item: TokenStream,
+}
+
+impl Parse for Import {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        bracketed!(content in input);
+        let mut imported = vec![];
+        while !content.is_empty() {
+            let name = content.parse()?;
+            let mut registries = vec![];
+            while content.peek(LitStr) {
+                registries.push(content.parse()?);
+            }
+            imported.push((name, registries));
+        }
+        let content;
+        bracketed!(content in input);
+        let pending = content.parse_terminated(Ident::parse, Token![,])?.into_iter().collect();
+        let content;
+        parenthesized!(content in input);
+        Ok(Import {
+            imported,
+            pending,
+            attributes: content.parse()?,
+            item: input.parse()?,
+        })
+    }
+}
+
+impl Import {
+    /// The items of every collected manifest, with paths starting with the name of their crate
+    pub fn items(&self) -> syn::Result<Vec<DiscoveredItem>> {
+        let mut items = vec![];
+        for (name, registry) in self
+            .imported
+            .iter()
+            .flat_map(|(name, registries)| registries.iter().map(move |registry| (name, registry)))
+        {
+            let error = |e: syn::Error| {
+                syn::Error::new(
+                    name.span(),
+                    format!("utoipauto: invalid manifest of `{name}`, is it built with the same utoipauto? {e}"),
+                )
+            };
+            for mut item in parse_registry(&registry.value()).map_err(error)? {
+                if let Some(first) = item.path.segments.first_mut().filter(|first| first.ident == "crate") {
+                    first.ident = Ident::new(&name.to_string(), name.span());
+                }
+                for candidate in item.references.iter_mut().flat_map(|r| r.candidates.iter_mut()) {
+                    if let Some(rest) = candidate.strip_prefix("crate::") {
+                        *candidate = format!("{}::{}", name, rest);
+                    }
+                }
+                item.span = name.span();
+                items.push(item);
+            }
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+
+    use super::{export_manifest, import_call, is_exported, Import};
+    use crate::builder::render_registry;
+    use crate::discover::Discoverer;
+
+    #[test]
+    fn test_export_import() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/invoices", responses((status = 200, body = Vec<Invoice>)))]
+            pub fn list_invoices() {}
+
+            #[utoipa::path(get, path = "/internal")]
+            fn internal() {}
+
+            #[derive(ToSchema)]
+            pub struct Invoice;
+
+            #[derive(ToSchema)]
+            pub(crate) struct Draft;
+
+            impl ToSchema for Amount {}
+
+            #[derive(ToSchema)]
+            pub struct Page<T>(T);
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let manifest = export_manifest(&items).to_string();
+        assert!(manifest.contains("macro_rules ! __utoipauto_manifest"));
+        assert!(manifest.contains(":: utoipauto :: __import !"));
+
+        // The input of `__import!` once the manifest is collected
+        let exported = items.into_iter().filter(is_exported).collect::<Vec<_>>();
+        let input = format!(
+            "[billing {:?}] [] (paths = \"./src\") struct ApiDoc;",
+            render_registry(&exported)
+        );
+        let import: Import = syn::parse_str(&input).unwrap();
+        let items = import.items().unwrap();
+        let paths = items.iter().map(|item| item.path_string()).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec!["billing::list_invoices", "billing::Invoice", "billing::Amount"]
+        );
+        // The referenced types are resolved in the imported crate
+        assert_eq!(items[0].references[0].candidates, vec!["billing::Invoice"]);
+        assert_eq!(import.attributes.to_string(), quote!(paths = "./src").to_string());
+    }
+
+    #[test]
+    fn test_export_cfg() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/invoices")]
+            pub fn list_invoices() {}
+
+            #[cfg(feature = "refunds")]
+            #[utoipa::path(get, path = "/refunds")]
+            pub fn list_refunds() {}
+
+            #[cfg(feature = "credit")]
+            #[derive(ToSchema)]
+            pub struct Credit;
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let manifest = export_manifest(&items).to_string();
+        // One macro for each predicate, with and without its registry, the last one calls `__import!`
+        assert!(manifest.contains("# [cfg (feature = \"refunds\")]"));
+        assert!(manifest.contains("# [cfg (not (feature = \"refunds\"))]"));
+        assert!(manifest.contains("# [cfg (not (feature = \"credit\"))]"));
+        assert_eq!(manifest.matches("macro_rules ! __utoipauto_manifest_cfg_0").count(), 2);
+        assert_eq!(manifest.matches("macro_rules ! __utoipauto_manifest_cfg_1").count(), 2);
+        assert_eq!(manifest.matches(":: utoipauto :: __import !").count(), 2);
+
+        // The registries of the enabled predicates follow the one of the items compiled unconditionally
+        let registries = [&items[..1], &items[1..2]].map(|items| format!("{:?}", render_registry(items)));
+        let input = format!("[billing {} {}] [] () struct ApiDoc;", registries[0], registries[1]);
+        let import: Import = syn::parse_str(&input).unwrap();
+        let paths = import
+            .items()
+            .unwrap()
+            .iter()
+            .map(|item| item.path_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["billing::list_invoices", "billing::list_refunds"]);
+    }
+
+    #[test]
+    fn test_private_modules_are_not_exported() {
+        let file: syn::File = syn::parse_quote! {
+            mod drafts {
+                #[utoipa::path(get, path = "/drafts")]
+                pub fn list_drafts() {}
+            }
+
+            pub mod invoices {
+                #[derive(ToSchema)]
+                pub struct Invoice;
+
+                mod internal {
+                    #[derive(ToSchema)]
+                    pub struct Ledger;
+                }
+            }
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let exported = items
+            .iter()
+            .filter(|item| is_exported(item))
+            .map(|item| item.path_string())
+            .collect::<Vec<_>>();
+        assert_eq!(exported, vec!["crate::invoices::Invoice"]);
+    }
+
+    #[test]
+    fn test_import_call() {
+        let attributes = quote!(import = [billing, shipping]);
+        let item = quote!(
+            struct ApiDoc;
+        );
+        let pending = [syn::parse_quote!(billing), syn::parse_quote!(shipping)];
+        assert_eq!(
+            import_call(&[], &pending, &attributes, &item).to_string(),
+            quote!(
+                billing::__utoipauto_manifest! { [] [billing, shipping] (import = [billing, shipping]) struct ApiDoc; }
+            )
+            .to_string()
+        );
+        assert_eq!(
+            import_call(&[], &[], &attributes, &item).to_string(),
+            quote!(::utoipauto::__import! { [] [] (import = [billing, shipping]) struct ApiDoc; }).to_string()
+        );
+    }
+}
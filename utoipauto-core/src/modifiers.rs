@@ -0,0 +1,217 @@
+//! `#[utoipauto::modifier]`: add the marked modifiers to `modifiers(...)`
+//!
+//! Only the marked unit structs are added, `modifiers(...)` takes a value and the other `impl utoipa::Modify` may
+//! belong to another documentation struct. The modifiers run in the order of `#[utoipauto::modifier(order = 1)]`, 0 by default, and in discovery order
+//! for the same `order`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::{Attribute, Fields, Ident, Item, LitInt, Meta, Token};
+
+use crate::discover::{DiscoveredItem, DiscoveredKind};
+use crate::rules::is_utoipauto_attribute;
+
+/// The arguments of `#[utoipauto::modifier(order = 1)]`
+struct ModifierArguments {
+    order: Option<i32>,
+}
+
+impl Parse for ModifierArguments {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut order = None;
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            if name != "order" {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("utoipauto: unknown modifier parameter `{}`, expected `order`", name),
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            let value = input.parse::<LitInt>()?;
+            if order.replace(value.base10_parse()?).is_some() {
+                return Err(syn::Error::new(value.span(), "utoipauto: `order` is given twice"));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self { order })
+    }
+}
+
+/// The `order` given to `#[utoipauto::modifier(...)]`, checked by the attribute macro
+pub fn modifier_arguments(attributes: TokenStream) -> syn::Result<Option<i32>> {
+    Ok(ModifierArguments::parse.parse2(attributes)?.order)
+}
+
+/// Check that `#[utoipauto::modifier]` is put on a unit struct, the only value `modifiers(...)` can take by its name
+pub fn check_modifier_item(item: TokenStream) -> syn::Result<()> {
+    match syn::parse2::<Item>(item)? {
+        Item::Struct(item) if matches!(item.fields, Fields::Unit) => Ok(()),
+        Item::Struct(item) => Err(syn::Error::new(
+            item.ident.span(),
+            format!(
+                "utoipauto: the modifier `{}` must be a unit struct, `modifiers(...)` takes a value",
+                item.ident
+            ),
+        )),
+        _ => Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "utoipauto: `#[utoipauto::modifier]` only applies to unit structs",
+        )),
+    }
+}
+
+/// The `order` of a modifier marked with `#[utoipauto::modifier(order = 1)]` among `attrs`
+pub fn modifier_order(attrs: &[Attribute]) -> syn::Result<Option<i32>> {
+    for attr in attrs.iter().filter(|attr| is_utoipauto_attribute(attr, "modifier")) {
+        if let Meta::List(list) = &attr.meta {
+            return modifier_arguments(list.tokens.clone());
+        }
+    }
+    Ok(None)
+}
+
+/// The discovered modifiers in the order they run, each one once
+///
+/// A modifier found twice, under several cfg predicates or marker rules, keeps the first `order` given
+pub fn ordered_modifiers<'a>(items: impl IntoIterator<Item = &'a DiscoveredItem>) -> Vec<(&'a DiscoveredItem, i32)> {
+    let mut modifiers: Vec<(&DiscoveredItem, Option<i32>)> = Vec::new();
+    for item in items
+        .into_iter()
+        .filter(|item| item.kind == DiscoveredKind::Modifier && item.is_discovered())
+    {
+        match modifiers
+            .iter_mut()
+            .find(|(other, _)| other.path_string() == item.path_string())
+        {
+            Some((_, order)) => *order = order.or(item.order),
+            None => modifiers.push((item, item.order)),
+        }
+    }
+    let mut modifiers = modifiers
+        .into_iter()
+        .map(|(item, order)| (item, order.unwrap_or_default()))
+        .collect::<Vec<_>>();
+    modifiers.sort_by_key(|(_, order)| *order);
+    modifiers
+}
+
+/// The `utoipa::Modify` running the discovered modifiers in their order, named `modifier`
+///
+/// Each modifier runs under its cfg predicate, `modifiers(...)` only takes values. The ones whose name is already
+/// listed in `existing` are left out, `None` if no modifier is left.
+pub fn discovered_modifiers(modifier: &Ident, existing: &[String], items: &[&DiscoveredItem]) -> Option<TokenStream> {
+    let modifiers = ordered_modifiers(items.iter().copied())
+        .into_iter()
+        .filter(|(item, _)| {
+            let name = item.path.segments.last().map(|segment| segment.ident.to_string());
+            !name.is_some_and(|name| existing.contains(&name))
+        })
+        .map(|(item, _)| {
+            let cfg = item.cfg.as_ref().map(|cfg| quote!(#[cfg(#cfg)]));
+            let path = &item.path;
+            quote!(#cfg <#path as ::utoipa::Modify>::modify(&#path, openapi);)
+        })
+        .collect::<Vec<_>>();
+    if modifiers.is_empty() {
+        return None;
+    }
+    Some(quote!(
+        #[allow(non_camel_case_types)]
+        struct #modifier;
+
+        impl ::utoipa::Modify for #modifier {
+            fn modify(&self, openapi: &mut ::utoipa::openapi::OpenApi) {
+                #(#modifiers)*
+            }
+        }
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_modifier_item, discovered_modifiers, modifier_arguments};
+    use crate::discover::Discoverer;
+
+    #[test]
+    fn test_discovered_modifiers() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipauto::modifier(order = 1)]
+            pub struct Servers;
+
+            impl utoipa::Modify for Servers {}
+
+            #[utoipauto::modifier]
+            pub struct SecurityAddon;
+
+            #[utoipauto::modifier]
+            pub struct Listed;
+
+            // Not marked, it may belong to another documentation struct
+            pub struct Unmarked;
+
+            impl utoipa::Modify for Unmarked {}
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        let modifier = syn::parse_quote!(__utoipauto_modifiers_ApiDoc);
+        let code = discovered_modifiers(&modifier, &["Listed".to_string()], &items)
+            .unwrap()
+            .to_string();
+        assert!(code.contains(
+            "< crate :: SecurityAddon as :: utoipa :: Modify > :: modify (& crate :: SecurityAddon , openapi) ; \
+             < crate :: Servers as :: utoipa :: Modify > :: modify (& crate :: Servers , openapi) ;"
+        ));
+        assert!(!code.contains("Listed"));
+        assert!(!code.contains("Unmarked"));
+    }
+
+    #[test]
+    fn test_modifier_with_fields() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipauto::modifier]
+            pub struct SecurityAddon {
+                scheme: String,
+            }
+
+            impl utoipa::Modify for SecurityAddon {}
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        let modifier = syn::parse_quote!(__utoipauto_modifiers_ApiDoc);
+        assert!(discovered_modifiers(&modifier, &[], &items).is_none());
+
+        let error = check_modifier_item(quote::quote!(
+            pub struct SecurityAddon {
+                scheme: String,
+            }
+        ))
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "utoipauto: the modifier `SecurityAddon` must be a unit struct, `modifiers(...)` takes a value"
+        );
+        assert!(check_modifier_item(quote::quote!(
+            pub struct SecurityAddon(String);
+        ))
+        .is_err());
+        assert!(check_modifier_item(quote::quote!(
+            pub struct SecurityAddon;
+        ))
+        .is_ok());
+    }
+
+    #[test]
+    fn test_modifier_arguments() {
+        assert_eq!(modifier_arguments(quote::quote!()).unwrap(), None);
+        assert_eq!(modifier_arguments(quote::quote!(order = -2)).unwrap(), Some(-2));
+        let error = modifier_arguments(quote::quote!(rank = 1)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "utoipauto: unknown modifier parameter `rank`, expected `order`"
+        );
+    }
+}
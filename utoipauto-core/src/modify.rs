@@ -0,0 +1,19 @@
+//! The code shared by the generated `utoipa::Modify` impls
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// An expression of type `&mut Option<Operation>`: the operation of the `PathItem` `item` for `method`,
+/// a `utoipa::openapi::path::HttpMethod`
+pub(crate) fn operation_slot(item: &TokenStream, method: &TokenStream) -> TokenStream {
+    quote!(match #method {
+        ::utoipa::openapi::path::HttpMethod::Get => &mut #item.get,
+        ::utoipa::openapi::path::HttpMethod::Post => &mut #item.post,
+        ::utoipa::openapi::path::HttpMethod::Put => &mut #item.put,
+        ::utoipa::openapi::path::HttpMethod::Delete => &mut #item.delete,
+        ::utoipa::openapi::path::HttpMethod::Options => &mut #item.options,
+        ::utoipa::openapi::path::HttpMethod::Head => &mut #item.head,
+        ::utoipa::openapi::path::HttpMethod::Patch => &mut #item.patch,
+        ::utoipa::openapi::path::HttpMethod::Trace => &mut #item.trace,
+    })
+}
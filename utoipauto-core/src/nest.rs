@@ -0,0 +1,208 @@
+//! `#[utoipauto::nest]` and `#[utoipauto::nest(path = "/users")]`: nest the documentation structs of the
+//! sub-domains
+//!
+//! The discovered structs are added to the `nest((path = "/users", api = crate::users::UsersApi))` of the
+//! documentation, and the handlers of their module are left out of its `paths(...)`, they are documented by the
+//! nested struct.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::{Attribute, Ident, LitStr, Meta, Token};
+
+use crate::discover::{DiscoveredItem, DiscoveredKind, SkipReason};
+use crate::rules::is_utoipauto_attribute;
+
+/// The arguments of `#[utoipauto::nest(path = "/users")]`
+struct NestArguments {
+    path: Option<String>,
+}
+
+impl Parse for NestArguments {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut path = None;
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            if name != "path" {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("utoipauto: unknown nest parameter `{}`, expected `path`", name),
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            let value = input.parse::<LitStr>()?;
+            if !value.value().starts_with('/') {
+                return Err(syn::Error::new(
+                    value.span(),
+                    "utoipauto: the nest path must start with `/`",
+                ));
+            }
+            if path.replace(value.value()).is_some() {
+                return Err(syn::Error::new(value.span(), "utoipauto: `path` is given twice"));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self { path })
+    }
+}
+
+/// The `path` given to `#[utoipauto::nest(...)]`, checked by the attribute macro
+pub fn nest_arguments(attributes: TokenStream) -> syn::Result<Option<String>> {
+    Ok(NestArguments::parse.parse2(attributes)?.path)
+}
+
+/// The path the documentation struct `path` is nested under
+///
+/// The `path` of `#[utoipauto::nest(path = "/users")]` among `attrs`, or `/<module name>`. The structs at the
+/// crate root without a `path` are not nested.
+pub fn nest_path(path: &syn::Path, attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    for attr in attrs.iter().filter(|attr| is_utoipauto_attribute(attr, "nest")) {
+        if let Meta::List(list) = &attr.meta {
+            if let Some(path) = nest_arguments(list.tokens.clone())? {
+                return Ok(Some(path));
+            }
+        }
+    }
+    let segments = path.segments.len();
+    Ok(match segments > 2 {
+        true => Some(format!("/{}", path.segments[segments - 2].ident)),
+        false => None,
+    })
+}
+
+/// Skip the handlers documented by a nested struct, and the modifiers that belong to it: the ones of its module and
+/// of its submodules
+///
+/// The structs at the crate root don't exclude any handler, they can't be told apart from the others. The
+/// handlers skipped by a previous call are reconsidered, for when a nested struct is removed.
+pub fn exclude_nested_handlers(items: &mut [DiscoveredItem]) {
+    for item in items.iter_mut() {
+        if item.skip_reason == Some(SkipReason::Nested) {
+            item.skip_reason = None;
+        }
+    }
+    let modules = items
+        .iter()
+        .filter(|item| item.kind == DiscoveredKind::Nest && item.is_discovered() && item.path.segments.len() > 2)
+        .map(|item| module_of(&item.path_string()).to_string())
+        .collect::<Vec<_>>();
+    for item in items
+        .iter_mut()
+        .filter(|item| matches!(item.kind, DiscoveredKind::Path | DiscoveredKind::Modifier) && item.is_discovered())
+    {
+        let path = item.path_string();
+        let module = module_of(&path);
+        let nested = modules
+            .iter()
+            .any(|nested| module == nested || module.starts_with(&format!("{}::", nested)));
+        if nested {
+            item.skip_reason = Some(SkipReason::Nested);
+        }
+    }
+}
+
+/// The `(path = "/users", api = crate::users::UsersApi)` entries to append to `nest(...)`
+///
+/// The structs whose name is already nested in `existing` are left out
+pub fn nest_entries(existing: &[String], items: &[&DiscoveredItem]) -> TokenStream {
+    let entries = items
+        .iter()
+        .filter(|item| item.kind == DiscoveredKind::Nest && item.is_discovered())
+        .filter(|item| {
+            let name = item.path.segments.last().map(|segment| segment.ident.to_string());
+            !name.is_some_and(|name| existing.contains(&name))
+        })
+        .filter_map(|item| {
+            let path = item.nest.as_ref()?;
+            let api = &item.path;
+            Some(quote!((path = #path, api = #api)))
+        });
+    quote!(#(#entries),*)
+}
+
+/// `crate::users` for `crate::users::list_users`
+fn module_of(path: &str) -> &str {
+    path.rsplit_once("::").map_or(path, |(module, _)| module)
+}
+
+#[cfg(test)]
+mod test {
+    use super::nest_entries;
+    use crate::discover::{to_token_streams, Discoverer, SkipReason};
+
+    #[test]
+    fn test_nested_documentation() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/health")]
+            pub fn health() {}
+
+            mod users {
+                #[utoipauto::nest]
+                #[derive(OpenApi)]
+                #[openapi(paths(handlers::list_users))]
+                pub struct UsersApi;
+
+                mod handlers {
+                    #[utoipa::path(get, path = "/")]
+                    pub fn list_users() {}
+                }
+
+                #[utoipauto::modifier]
+                pub struct UsersServers;
+            }
+
+            mod orders {
+                #[utoipauto::nest(path = "/api/orders")]
+                #[derive(OpenApi)]
+                pub struct OrdersApi;
+
+                #[utoipa::path(get, path = "/")]
+                pub fn list_orders() {}
+            }
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let skipped = items
+            .iter()
+            .filter(|item| item.skip_reason == Some(SkipReason::Nested))
+            .map(|item| item.path_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            skipped,
+            vec![
+                "crate::users::handlers::list_users",
+                "crate::users::UsersServers",
+                "crate::orders::list_orders"
+            ]
+        );
+
+        let items = items.iter().collect::<Vec<_>>();
+        assert_eq!(
+            nest_entries(&["OrdersApi".to_string()], &items).to_string(),
+            "(path = \"/users\" , api = crate :: users :: UsersApi)"
+        );
+        assert_eq!(
+            nest_entries(&[], &items).to_string(),
+            "(path = \"/users\" , api = crate :: users :: UsersApi) , (path = \"/api/orders\" , api = crate :: orders :: OrdersApi)"
+        );
+    }
+
+    #[test]
+    fn test_plain_documentation_is_not_nested() {
+        let file: syn::File = syn::parse_quote! {
+            mod users {
+                #[derive(OpenApi)]
+                #[openapi(paths(list_users))]
+                pub struct UsersApi;
+
+                #[utoipa::path(get, path = "/users")]
+                pub fn list_users() {}
+            }
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let (paths, _, _) = to_token_streams(&items);
+        assert_eq!(paths.to_string(), "crate :: users :: list_users");
+        assert!(nest_entries(&[], &items.iter().collect::<Vec<_>>()).is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! The operation ids of the discovered handlers
+//!
+//! utoipa uses the name of the function when `#[utoipa::path]` has no `operation_id`, so two handlers with the same
+//! name in different modules get the same id, which breaks the generated clients. The duplicates are a compile
+//! error, `operation_id = "module_path"` prefixes the default ids with the module of the handler:
+//! `crate::users::list` becomes `users_list`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+use crate::discover::{DiscoveredItem, DiscoveredKind};
+use crate::modify::operation_slot;
+use crate::prefixes::documented_path;
+use crate::token_utils::{combine_error, Prefix};
+
+/// The operation id of the handler `item`
+///
+/// The `operation_id` of `#[utoipa::path]`, or the default one: the name of the function, prefixed with its module
+/// below the crate root with `module_path`
+///
+/// `None` when the `operation_id` is an expression, its value isn't known
+pub fn operation_id(item: &DiscoveredItem, module_path: bool) -> Option<String> {
+    if let Some(route) = &item.route {
+        if route.expression_operation_id {
+            return None;
+        }
+        if let Some(operation_id) = &route.operation_id {
+            return Some(operation_id.clone());
+        }
+    }
+    let segments = item
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>();
+    let (name, modules) = segments.split_last()?;
+    Some(match (module_path, modules) {
+        (true, [_, modules @ ..]) if !modules.is_empty() => format!("{}_{}", modules.join("_"), name),
+        _ => name.clone(),
+    })
+}
+
+/// An error for each operation id shared by two discovered handlers, with the location of both
+///
+/// The handlers whose `operation_id` is an expression are not checked, nor the handlers whose cfg predicates can't be
+/// enabled together
+pub fn check_operation_ids(items: &[&DiscoveredItem], module_path: bool) -> syn::Result<()> {
+    let mut errors: Option<syn::Error> = None;
+    let mut seen: Vec<(String, &DiscoveredItem)> = Vec::new();
+    for item in handlers(items) {
+        let Some(id) = operation_id(item, module_path) else {
+            continue;
+        };
+        match seen
+            .iter()
+            .find(|(other, first)| *other == id && item.compiled_with(first))
+        {
+            Some((_, first)) => {
+                let hint = match module_path {
+                    true => "give one of them another `operation_id`",
+                    false => "give one of them an `operation_id`, or use `operation_id = \"module_path\"`",
+                };
+                combine_error(
+                    &mut errors,
+                    syn::Error::new(
+                        item.span,
+                        format!(
+                            "utoipauto: the operation id `{}` of `{}` ({}) is already used by `{}` ({}), {}",
+                            id,
+                            item.path_string(),
+                            item.location(),
+                            first.path_string(),
+                            first.location(),
+                            hint
+                        ),
+                    ),
+                );
+            }
+            None => seen.push((id, item)),
+        }
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+/// The `utoipa::Modify` setting the module qualified operation ids of `operation_id = "module_path"`, named
+/// `modifier`
+///
+/// Only the handlers without an `operation_id`, literal or expression, are changed
+pub fn operation_ids_modifier(modifier: &Ident, prefixes: &[Prefix], items: &[&DiscoveredItem]) -> TokenStream {
+    let renamed = handlers(items)
+        .filter(|item| {
+            item.route.as_ref().map_or(true, |route| {
+                route.operation_id.is_none() && !route.expression_operation_id
+            })
+        })
+        .filter_map(|item| {
+            let id = operation_id(item, true)?;
+            let handler = crate::expression::path_struct(&item.path);
+            let path = documented_path(prefixes, item);
+            let cfg = item.cfg.as_ref().map(|cfg| quote!(#[cfg(#cfg)]));
+            Some(quote!(#cfg operation_id::<#handler>(openapi, #path, #id);))
+        });
+    let operation = operation_slot(&quote!(item), &quote!(method));
+    quote!(
+        #[allow(non_camel_case_types)]
+        struct #modifier;
+
+        impl ::utoipa::Modify for #modifier {
+            fn modify(&self, openapi: &mut ::utoipa::openapi::OpenApi) {
+                fn operation_id<P: ::utoipa::Path>(openapi: &mut ::utoipa::openapi::OpenApi, path: &str, id: &str) {
+                    let Some(item) = openapi.paths.paths.get_mut(path) else {
+                        return;
+                    };
+                    for method in P::methods() {
+                        if let Some(operation) = #operation {
+                            operation.operation_id = Some(id.to_string());
+                        }
+                    }
+                }
+
+                #(#renamed)*
+            }
+        }
+    )
+}
+
+fn handlers<'a>(items: &'a [&'a DiscoveredItem]) -> impl Iterator<Item = &'a DiscoveredItem> {
+    items
+        .iter()
+        .copied()
+        .filter(|item| item.kind == DiscoveredKind::Path && item.is_discovered())
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+
+    use super::{check_operation_ids, operation_ids_modifier};
+    use crate::discover::Discoverer;
+    use crate::token_utils::Prefix;
+
+    fn items() -> Vec<crate::discover::DiscoveredItem> {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/health")]
+            pub fn health() {}
+
+            mod users {
+                #[utoipa::path(get, path = "/users")]
+                pub fn list() {}
+
+                #[utoipa::path(get, path = "/users/{id}", operation_id = "get_user")]
+                pub fn get() {}
+            }
+
+            mod orders {
+                #[utoipa::path(get, path = "/orders")]
+                pub fn list() {}
+            }
+
+            mod invoices {
+                #[utoipa::path(get, path = "/invoices", operation_id = LIST_INVOICES)]
+                pub fn list() {}
+            }
+
+            mod payments {
+                #[utoipa::path(get, path = "/payments", operation_id = LIST_PAYMENTS)]
+                pub fn list() {}
+            }
+        };
+        Discoverer::new().file("./src/lib.rs", file).discover().unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_operation_ids() {
+        let items = items();
+        let items = items.iter().collect::<Vec<_>>();
+        let error = check_operation_ids(&items, false).unwrap_err();
+        // The `operation_id` expressions of `invoices::list` and `payments::list` are not known
+        assert_eq!(error.clone().into_iter().count(), 1);
+        assert_eq!(
+            error.to_string(),
+            "utoipauto: the operation id `list` of `crate::orders::list` (./src/lib.rs) is already used by \
+             `crate::users::list` (./src/lib.rs), give one of them an `operation_id`, or use \
+             `operation_id = \"module_path\"`"
+        );
+        assert!(check_operation_ids(&items, true).is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_operation_ids_cfg() {
+        let file: syn::File = syn::parse_quote! {
+            #[cfg(feature = "fast")]
+            #[utoipa::path(get, path = "/status")]
+            pub fn status() {}
+
+            #[cfg(not(feature = "fast"))]
+            #[utoipa::path(get, path = "/status")]
+            pub fn status() {}
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        assert!(check_operation_ids(&items, false).is_ok());
+    }
+
+    #[test]
+    fn test_operation_ids_modifier() {
+        let items = items();
+        let items = items.iter().collect::<Vec<_>>();
+        let modifier = syn::parse_quote!(__utoipauto_operation_ids_ApiDoc);
+        let prefixes = [Prefix {
+            module: "crate::orders".to_string(),
+            prefix: "/api".to_string(),
+        }];
+        let code = operation_ids_modifier(&modifier, &prefixes, &items).to_string();
+        assert!(code.contains(
+            &quote!(operation_id::<crate::users::__path_list>(
+                openapi,
+                &<crate::users::__path_list as ::utoipa::Path>::path(),
+                "users_list"
+            );)
+            .to_string()
+        ));
+        assert!(code.contains("operation_id :: < crate :: __path_health > (openapi , & < crate :: __path_health"));
+        // The operations of a prefixed module are found at the prefixed path
+        assert!(code.contains(
+            &quote!(operation_id::<crate::orders::__path_list>(
+                openapi,
+                &match <crate::orders::__path_list as ::utoipa::Path>::path().as_str() {
+                    "/" => "/api".to_string(),
+                    path => ::std::format!("{}{}", "/api", path),
+                },
+                "orders_list"
+            );)
+            .to_string()
+        ));
+        assert!(!code.contains("__path_get"));
+        assert!(!code.contains("invoices"));
+        assert!(!code.contains("payments"));
+    }
+}
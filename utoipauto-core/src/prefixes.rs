@@ -0,0 +1,227 @@
+//! `prefixes = { "crate::api::v1" = "/api/v1" }`: document the handlers of a module under its route prefix
+//!
+//! The handlers declare paths relative to their module, so the handlers of two prefixes can share a method and a
+//! path. They are documented apart, by a hidden documentation struct for each prefix, and a generated
+//! `utoipa::Modify` nests each of these documentations under its prefix, like the generated routers serve them. A
+//! path item is nested whole, its `summary`, `description`, `servers` and `parameters` included.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+
+use crate::discover::{DiscoveredItem, DiscoveredKind};
+use crate::modify::operation_slot;
+use crate::token_utils::Prefix;
+
+/// Whether the item is a handler documented under a prefix, it is left out of `paths(...)`
+pub fn is_prefixed(prefixes: &[Prefix], item: &DiscoveredItem) -> bool {
+    item.kind == DiscoveredKind::Path && Prefix::find(prefixes, &item.path_string()).is_some()
+}
+
+/// An expression of type `&String`: the path the operations of the handler `item` are documented at, with the prefix
+/// of its module
+pub(crate) fn documented_path(prefixes: &[Prefix], item: &DiscoveredItem) -> TokenStream {
+    let handler = crate::expression::path_struct(&item.path);
+    let path = quote!(<#handler as ::utoipa::Path>::path());
+    match Prefix::find(prefixes, &item.path_string()) {
+        Some(prefix) => {
+            let prefix = &prefix.prefix;
+            quote!(&match #path.as_str() {
+                "/" => #prefix.to_string(),
+                path => ::std::format!("{}{}", #prefix, path),
+            })
+        }
+        None => quote!(&#path),
+    }
+}
+
+/// The documentation struct of the handlers of each prefix, and the `utoipa::Modify` nesting them, named `modifier`
+///
+/// The handlers of a prefix under different cfg predicates get a struct each, under their predicate: the structs are
+/// named after the modifier, `__utoipauto_prefixes_ApiDoc_0` for `__utoipauto_prefixes_ApiDoc`
+pub fn prefixes_modifier(modifier: &Ident, prefixes: &[Prefix], items: &[&DiscoveredItem]) -> TokenStream {
+    let mut groups: Vec<(&str, Option<&TokenStream>, Vec<&syn::Path>)> = vec![];
+    for item in items
+        .iter()
+        .filter(|item| item.kind == DiscoveredKind::Path && item.is_discovered())
+    {
+        let Some(prefix) = Prefix::find(prefixes, &item.path_string()) else {
+            continue;
+        };
+        let prefix = prefix.prefix.as_str();
+        let cfg = item.cfg.as_ref().map(ToString::to_string);
+        let group = groups
+            .iter_mut()
+            .find(|(other, other_cfg, _)| *other == prefix && other_cfg.map(ToString::to_string) == cfg);
+        match group {
+            Some((_, _, handlers)) => handlers.push(&item.path),
+            None => groups.push((prefix, item.cfg.as_ref(), vec![&item.path])),
+        }
+    }
+    let mut definitions = vec![];
+    let mut nests = vec![];
+    for (i, (prefix, cfg, handlers)) in groups.into_iter().enumerate() {
+        let cfg = cfg.map(|cfg| quote!(#[cfg(#cfg)]));
+        let ident = format_ident!("{}_{}", modifier, i);
+        definitions.push(quote!(
+            #cfg
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #[derive(::utoipa::OpenApi)]
+            #[openapi(paths(#(#handlers),*))]
+            struct #ident;
+        ));
+        nests.push(quote!(#cfg nest(openapi, #prefix, <#ident as ::utoipa::OpenApi>::openapi());));
+    }
+    let moved_operation = operation_slot(&quote!(item), &quote!(method));
+    let existing_operation = operation_slot(&quote!(existing), &quote!(method));
+    quote!(
+        #(#definitions)*
+
+        #[allow(non_camel_case_types)]
+        struct #modifier;
+
+        impl ::utoipa::Modify for #modifier {
+            fn modify(&self, openapi: &mut ::utoipa::openapi::OpenApi) {
+                fn nest(
+                    openapi: &mut ::utoipa::openapi::OpenApi,
+                    prefix: &str,
+                    mut nested: ::utoipa::openapi::OpenApi,
+                ) {
+                    use ::utoipa::openapi::path::HttpMethod;
+
+                    let paths = ::std::mem::take(&mut nested.paths.paths);
+                    for (path, mut item) in paths {
+                        let prefixed = match path.as_str() {
+                            "/" => prefix.to_string(),
+                            path => ::std::format!("{prefix}{path}"),
+                        };
+                        let Some(existing) = openapi.paths.paths.get_mut(&prefixed) else {
+                            openapi.paths.paths.insert(prefixed, item);
+                            continue;
+                        };
+                        // Merged into the path item of a handler documented at the same path
+                        for method in &[
+                            HttpMethod::Get,
+                            HttpMethod::Post,
+                            HttpMethod::Put,
+                            HttpMethod::Delete,
+                            HttpMethod::Options,
+                            HttpMethod::Head,
+                            HttpMethod::Patch,
+                            HttpMethod::Trace,
+                        ] {
+                            if let Some(operation) = #moved_operation.take() {
+                                *#existing_operation = Some(operation);
+                            }
+                        }
+                        existing.summary = existing.summary.take().or(item.summary);
+                        existing.description = existing.description.take().or(item.description);
+                        for server in item.servers.into_iter().flatten() {
+                            let servers = existing.servers.get_or_insert_with(::std::vec::Vec::new);
+                            if !servers.contains(&server) {
+                                servers.push(server);
+                            }
+                        }
+                        for parameter in item.parameters.into_iter().flatten() {
+                            let parameters = existing.parameters.get_or_insert_with(::std::vec::Vec::new);
+                            if !parameters.contains(&parameter) {
+                                parameters.push(parameter);
+                            }
+                        }
+                        if let Some(extensions) = item.extensions {
+                            existing
+                                .extensions
+                                .get_or_insert_with(::std::default::Default::default)
+                                .merge(extensions);
+                        }
+                    }
+                    // The schemas referenced by the handlers
+                    openapi.merge(nested);
+                }
+
+                #(#nests)*
+            }
+        }
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+
+    use super::prefixes_modifier;
+    use crate::discover::Discoverer;
+    use crate::token_utils::Prefix;
+
+    #[test]
+    fn test_prefixes_modifier() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/health")]
+            pub fn health() {}
+
+            mod v1 {
+                #[utoipa::path(get, path = "/users")]
+                pub fn v1_users() {}
+
+                #[cfg(feature = "admin")]
+                #[utoipa::path(delete, path = "/users")]
+                pub fn delete_users() {}
+            }
+
+            mod v2 {
+                #[utoipa::path(get, path = "/users")]
+                pub fn v2_users() {}
+            }
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        let prefixes = [
+            Prefix {
+                module: "crate::v1".to_string(),
+                prefix: "/api/v1".to_string(),
+            },
+            Prefix {
+                module: "crate::v2".to_string(),
+                prefix: "/api/v2".to_string(),
+            },
+        ];
+        let modifier = syn::parse_quote!(__utoipauto_prefixes_ApiDoc);
+        let code = prefixes_modifier(&modifier, &prefixes, &items).to_string();
+        // The two `GET /users` are documented apart, they can't collide before they are prefixed
+        assert!(code.contains(
+            &quote!(
+                #[doc(hidden)]
+                #[allow(non_camel_case_types)]
+                #[derive(::utoipa::OpenApi)]
+                #[openapi(paths(crate::v1::v1_users))]
+                struct __utoipauto_prefixes_ApiDoc_0;
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote!(
+                #[cfg(feature = "admin")]
+                #[doc(hidden)]
+                #[allow(non_camel_case_types)]
+                #[derive(::utoipa::OpenApi)]
+                #[openapi(paths(crate::v1::delete_users))]
+                struct __utoipauto_prefixes_ApiDoc_1;
+            )
+            .to_string()
+        ));
+        assert!(code.contains("# [openapi (paths (crate :: v2 :: v2_users))] struct __utoipauto_prefixes_ApiDoc_2 ;"));
+        assert!(code.contains(
+            &quote!(
+                nest(openapi, "/api/v1", <__utoipauto_prefixes_ApiDoc_0 as ::utoipa::OpenApi>::openapi());
+                #[cfg(feature = "admin")]
+                nest(openapi, "/api/v1", <__utoipauto_prefixes_ApiDoc_1 as ::utoipa::OpenApi>::openapi());
+                nest(openapi, "/api/v2", <__utoipauto_prefixes_ApiDoc_2 as ::utoipa::OpenApi>::openapi());
+            )
+            .to_string()
+        ));
+        assert!(!code.contains("health"));
+        // A path item is merged into the one already at the prefixed path
+        assert!(code.contains("existing . summary = existing . summary . take () . or (item . summary) ;"));
+    }
+}
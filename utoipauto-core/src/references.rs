@@ -0,0 +1,861 @@
+//! The types referenced by the `body`, `request_body`, `response`, `content` and `params` arguments of the handlers
+//!
+//! A type that isn't a discovered schema or response, a known primitive or container, nor listed in
+//! `known_types = [...]` ends as a dangling `$ref` or as an error far from the handler, the macro warns about it at the
+//! handler instead.
+//!
+//! `schemas = "referenced"` and `responses = "referenced"` keep only the schemas and responses reachable from the
+//! handlers, through these arguments and the fields of the structs and enums.
+
+use std::collections::HashSet;
+
+use proc_macro2::{Delimiter, Ident, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{Attribute, Fields, GenericArgument, Item, Meta, PathArguments, Type, UseTree};
+
+use crate::discover::{path_without_generics, DiscoveredItem, DiscoveredKind};
+use crate::token_utils::warning;
+
+/// The types with a schema of their own in utoipa
+const PRIMITIVES: &[&str] = &[
+    "bool",
+    "char",
+    "str",
+    "String",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "f32",
+    "f64",
+    "Value",
+    "Uuid",
+    "Ulid",
+    "Url",
+    "Decimal",
+    "Date",
+    "DateTime",
+    "NaiveDate",
+    "NaiveTime",
+    "NaiveDateTime",
+    "OffsetDateTime",
+    "PrimitiveDateTime",
+    "Duration",
+    "Bytes",
+];
+
+/// The generic types documented as their type arguments
+const CONTAINERS: &[&str] = &[
+    "Vec",
+    "VecDeque",
+    "LinkedList",
+    "Option",
+    "Box",
+    "Rc",
+    "Arc",
+    "Cow",
+    "RefCell",
+    "HashMap",
+    "BTreeMap",
+    "IndexMap",
+    "HashSet",
+    "BTreeSet",
+    "IndexSet",
+];
+
+/// The argument names followed by a type: `body = User`
+const TYPE_ARGUMENTS: &[&str] = &["body", "request_body", "response", "content"];
+
+/// The schemas or the responses added to the documentation
+///
+/// `schemas = "referenced"`: the ones reachable from the discovered handlers, and not already collected by utoipa
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Selection {
+    /// Every discovered one
+    #[default]
+    All,
+    /// The ones reachable from the discovered handlers that utoipa doesn't collect by itself
+    Referenced,
+    /// None of them, utoipa only collects the schemas of the request and response bodies
+    None,
+}
+
+impl Selection {
+    /// The values of `schemas = "..."` and `responses = "..."`
+    pub const CHOICES: &'static [(&'static str, Selection)] = &[
+        ("all", Selection::All),
+        ("referenced", Selection::Referenced),
+        ("none", Selection::None),
+    ];
+
+    /// The selection named `value`, one of [`Selection::names`]
+    pub fn from_name(value: &str) -> Option<Selection> {
+        Self::CHOICES
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map(|(_, selection)| *selection)
+    }
+
+    /// The names accepted by `schemas = "..."` and `responses = "..."`
+    pub fn names() -> Vec<&'static str> {
+        Self::CHOICES.iter().map(|(name, _)| *name).collect()
+    }
+}
+
+/// Where a type is referenced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// `body = User`, `request_body = User` or `content = User`, with `inline(User)` or not: utoipa collects the
+    /// schemas it references
+    Body,
+    /// `response = NotFound`, a `ToResponse` type
+    Response,
+    /// `params(("id" = UserId, Path))`
+    Param,
+    /// The type of a field of a struct or an enum, or its `#[schema(value_type = ...)]`
+    Field,
+}
+
+impl Position {
+    /// The names of the positions in the registry format
+    pub const NAMES: &'static [(&'static str, Position)] = &[
+        ("body", Position::Body),
+        ("response", Position::Response),
+        ("param", Position::Param),
+        ("field", Position::Field),
+    ];
+
+    /// `body` for [`Position::Body`]
+    pub fn name(&self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(_, position)| position == self)
+            .map(|(name, _)| *name)
+            .expect("every position has a name")
+    }
+}
+
+/// A type referenced by a handler or by a field, with the full paths it may resolve to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeReference {
+    pub position: Position,
+    /// The path as written, without its generic arguments: `models::User`
+    pub name: String,
+    /// The full paths it may be, found with the `use` declarations of its module: `crate::models::User`
+    pub candidates: Vec<String>,
+}
+
+impl TypeReference {
+    /// `true` if the reference may be the item of path `key`, generics excluded
+    fn is(&self, key: &str) -> bool {
+        self.candidates.iter().any(|candidate| candidate == key)
+    }
+}
+
+/// The names brought in scope by the `use` declarations of a module, to resolve the types it references
+pub struct Imports {
+    /// `["crate", "api", "users"]`
+    module: Vec<String>,
+    /// The name in scope and a full path it may be: `("Dto", "crate::models::UserDto")`
+    names: Vec<(String, String)>,
+    /// The full paths the glob imports may be: `crate::models` for `use super::models::*;`
+    globs: Vec<String>,
+}
+
+impl Imports {
+    /// The `use` declarations among the items of the module `module`
+    pub fn new(module: &syn::Path, items: &[Item]) -> Self {
+        let mut imports = Self {
+            module: module
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect(),
+            names: vec![],
+            globs: vec![],
+        };
+        for item in items {
+            if let Item::Use(item) = item {
+                imports.add_use(&item.tree, item.leading_colon.is_some(), &mut vec![]);
+            }
+        }
+        imports
+    }
+
+    fn add_use(&mut self, tree: &UseTree, leading_colon: bool, prefix: &mut Vec<String>) {
+        match tree {
+            UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                self.add_use(&path.tree, leading_colon, prefix);
+                prefix.pop();
+            }
+            UseTree::Name(name) => self.add_name(leading_colon, prefix, &name.ident, &name.ident),
+            UseTree::Rename(rename) if rename.rename != "_" => {
+                self.add_name(leading_colon, prefix, &rename.ident, &rename.rename)
+            }
+            UseTree::Rename(_) => {}
+            UseTree::Glob(_) => {
+                let globs = self.resolve_use(leading_colon, prefix);
+                self.globs.extend(globs);
+            }
+            UseTree::Group(group) => group
+                .items
+                .iter()
+                .for_each(|tree| self.add_use(tree, leading_colon, prefix)),
+        }
+    }
+
+    /// `use models::{self, User as Dto};`
+    fn add_name(&mut self, leading_colon: bool, prefix: &[String], ident: &Ident, alias: &Ident) {
+        let mut path = prefix.to_vec();
+        if ident != "self" {
+            path.push(ident.to_string());
+        }
+        let alias = match alias == "self" {
+            true => prefix.last().cloned().unwrap_or_default(),
+            false => alias.to_string(),
+        };
+        for candidate in self.resolve_use(leading_colon, &path) {
+            self.names.push((alias.clone(), candidate));
+        }
+    }
+
+    /// The full paths of a `use` path: a module of the crate, or an external crate
+    fn resolve_use(&self, leading_colon: bool, segments: &[String]) -> Vec<String> {
+        let Some(first) = segments.first() else {
+            return vec![];
+        };
+        let supers = segments.iter().take_while(|segment| *segment == "super").count();
+        if leading_colon {
+            vec![segments.join("::")]
+        } else if first == "crate" {
+            vec![self.joined(&self.module[..1], &segments[1..])]
+        } else if first == "self" {
+            vec![self.joined(&self.module, &segments[1..])]
+        } else if supers > 0 {
+            let parent = self.module.len().saturating_sub(supers).max(1);
+            vec![self.joined(&self.module[..parent], &segments[supers..])]
+        } else {
+            vec![self.joined(&self.module, segments), segments.join("::")]
+        }
+    }
+
+    /// The full paths `path` may be in the module
+    pub fn resolve(&self, path: &syn::Path) -> Vec<String> {
+        let segments = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+        let Some(first) = segments.first() else {
+            return vec![];
+        };
+        let mut candidates = self
+            .names
+            .iter()
+            .filter(|(name, _)| name == first)
+            .map(|(_, target)| {
+                [target.as_str()]
+                    .into_iter()
+                    .chain(segments[1..].iter().map(String::as_str))
+            })
+            .map(|target| target.collect::<Vec<_>>().join("::"))
+            .collect::<Vec<_>>();
+        // A name brought in by `use` shadows the glob imports and the items of the module
+        if candidates.is_empty() {
+            candidates = self.resolve_use(path.leading_colon.is_some(), &segments);
+            if path.leading_colon.is_none() && !["crate", "self", "super"].contains(&first.as_str()) {
+                candidates.extend(
+                    self.globs
+                        .iter()
+                        .map(|glob| format!("{}::{}", glob, segments.join("::"))),
+                );
+            }
+        }
+        // A single segment is never the full path of an item
+        candidates.retain(|candidate| candidate.contains("::"));
+        candidates.dedup();
+        candidates
+    }
+
+    fn joined(&self, prefix: &[String], segments: &[String]) -> String {
+        prefix.iter().chain(segments).cloned().collect::<Vec<_>>().join("::")
+    }
+}
+
+/// The types referenced by the `#[utoipa::path(...)]` attributes among `attrs`, resolved in the module of `imports`
+pub fn handler_references(attrs: &[Attribute], fn_attribute_name: &str, imports: &Imports) -> Vec<TypeReference> {
+    let mut types = vec![];
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().segments.iter().any(|s| s.ident == fn_attribute_name))
+    {
+        if let Ok(list) = attr.meta.require_list() {
+            collect_types(&list.tokens, false, &mut types);
+        }
+    }
+    let mut references = vec![];
+    for (position, ty) in types {
+        collect_references(&ty, position, imports, &mut references);
+    }
+    references
+}
+
+/// The types of `fields`, or the ones given with `#[schema(value_type = ...)]`, resolved in the module of `imports`
+pub fn field_references(fields: &Fields, imports: &Imports) -> Vec<TypeReference> {
+    let mut references = vec![];
+    for field in fields {
+        let value_type = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("schema"))
+            .filter_map(|attr| match &attr.meta {
+                Meta::List(list) => Some(list.tokens.clone()),
+                _ => None,
+            })
+            .flat_map(|tokens| split_entries(&tokens))
+            .find_map(|entry| match entry.as_slice() {
+                [TokenTree::Ident(name), TokenTree::Punct(eq), rest @ ..]
+                    if name == "value_type" && eq.as_char() == '=' =>
+                {
+                    syn::parse2::<Type>(rest.iter().cloned().collect()).ok()
+                }
+                _ => None,
+            });
+        let ty = value_type.unwrap_or_else(|| field.ty.clone());
+        collect_references(&ty, Position::Field, imports, &mut references);
+    }
+    references
+}
+
+/// The schemas and responses of `items` to add to the documentation
+///
+/// With `Selection::Referenced`, the responses given to `response = ...` are added. The schemas referenced by the
+/// `params(...)` of the handlers and by the fields of these responses are added too, unless utoipa collects them
+/// from a request or response body, or from the fields of another added schema. The handlers and the other items
+/// are kept.
+pub fn selected_items<'a>(
+    items: &[&'a DiscoveredItem],
+    schemas: Selection,
+    responses: Selection,
+) -> Vec<&'a DiscoveredItem> {
+    if schemas == Selection::All && responses == Selection::All {
+        return items.to_vec();
+    }
+    let discovered = |kind: DiscoveredKind| {
+        items
+            .iter()
+            .copied()
+            .filter(move |item| item.kind == kind && item.is_discovered())
+    };
+    // The discovered item of `kind` a reference is, if any
+    let resolve = |kind: DiscoveredKind, reference: &TypeReference| {
+        discovered(kind).find(|item| reference.is(&path_without_generics(&item.path)))
+    };
+    let fields = |item: &DiscoveredItem| {
+        item.references
+            .iter()
+            .filter(|reference| reference.position == Position::Field)
+            .filter_map(|reference| resolve(DiscoveredKind::Schema, reference))
+            .map(|schema| path_without_generics(&schema.path))
+            .collect::<Vec<_>>()
+    };
+    // Every schema reachable from `roots` through the fields of the schemas
+    let reachable = |roots: Vec<String>| {
+        let mut reached = HashSet::new();
+        let mut pending = roots;
+        while let Some(key) = pending.pop() {
+            if reached.insert(key.clone()) {
+                let schema = discovered(DiscoveredKind::Schema).find(|item| path_without_generics(&item.path) == key);
+                pending.extend(schema.map(fields).unwrap_or_default());
+            }
+        }
+        reached
+    };
+
+    let handler_references = discovered(DiscoveredKind::Path)
+        .flat_map(|item| &item.references)
+        .collect::<Vec<_>>();
+    let referenced = |kind: DiscoveredKind, position: Position| {
+        let mut referenced = vec![];
+        for reference in handler_references
+            .iter()
+            .filter(|reference| reference.position == position)
+        {
+            if let Some(item) = resolve(kind, reference) {
+                let key = path_without_generics(&item.path);
+                if !referenced.contains(&key) {
+                    referenced.push(key);
+                }
+            }
+        }
+        referenced
+    };
+    let referenced_responses = referenced(DiscoveredKind::Response, Position::Response);
+
+    // The schemas that utoipa doesn't collect by itself, without the ones reachable from another one
+    let mut collected = reachable(referenced(DiscoveredKind::Schema, Position::Body));
+    let mut candidates = referenced(DiscoveredKind::Schema, Position::Param);
+    candidates.extend(
+        discovered(DiscoveredKind::Response)
+            .filter(|item| referenced_responses.contains(&path_without_generics(&item.path)))
+            .flat_map(fields),
+    );
+    let mut registered = HashSet::new();
+    for key in candidates {
+        if !collected.contains(&key) {
+            collected.extend(reachable(vec![key.clone()]));
+            registered.insert(key);
+        }
+    }
+
+    items
+        .iter()
+        .copied()
+        .filter(|item| match (item.kind, schemas, responses) {
+            (DiscoveredKind::Schema, Selection::All, _) | (DiscoveredKind::Response, _, Selection::All) => true,
+            (DiscoveredKind::Schema, Selection::Referenced, _) => {
+                item.is_discovered() && registered.contains(&path_without_generics(&item.path))
+            }
+            (DiscoveredKind::Response, _, Selection::Referenced) => {
+                item.is_discovered() && referenced_responses.contains(&path_without_generics(&item.path))
+            }
+            (DiscoveredKind::Schema | DiscoveredKind::Response, _, _) => false,
+            _ => true,
+        })
+        .collect()
+}
+
+/// A compiler warning for each type referenced by a discovered handler that isn't documented
+///
+/// A reference is documented if one of the full paths it may be is a discovered schema or response, the skipped ones
+/// excluded. The `known_types` with a path, like `chrono::Duration`, are compared with these full paths, and the
+/// other ones with the last segment of the reference.
+pub fn unresolved_references_warnings(items: &[DiscoveredItem], known_types: &[String]) -> TokenStream {
+    let documented = items
+        .iter()
+        .filter(|item| matches!(item.kind, DiscoveredKind::Schema | DiscoveredKind::Response))
+        .filter(|item| item.is_discovered())
+        .map(|item| path_without_generics(&item.path))
+        .collect::<HashSet<_>>();
+    let is_known = |reference: &TypeReference| {
+        reference
+            .candidates
+            .iter()
+            .any(|candidate| documented.contains(candidate))
+            || known_types.iter().any(|known| match known.contains("::") {
+                true => reference.name == *known || reference.is(known),
+                false => reference.name.rsplit("::").next() == Some(known.as_str()),
+            })
+    };
+
+    let mut warnings = TokenStream::new();
+    for item in items
+        .iter()
+        .filter(|item| item.kind == DiscoveredKind::Path && item.is_discovered())
+    {
+        let mut reported = HashSet::new();
+        for reference in item
+            .references
+            .iter()
+            .filter(|reference| reference.position != Position::Field)
+        {
+            if is_known(reference) || !reported.insert(&reference.name) {
+                continue;
+            }
+            let message = format!(
+                "utoipauto: the type `{}` referenced by `{}` ({}) is not a discovered schema or response, \
+                 derive `ToSchema` for it or add it to `known_types`",
+                reference.name,
+                item.path_string(),
+                item.location()
+            );
+            warnings.extend(warning(&message, item.span, item.cfg.as_ref(), false));
+        }
+    }
+    warnings
+}
+
+/// Walk the arguments of the attribute, `params` tells if they are the entries of `params(...)`
+fn collect_types(tokens: &TokenStream, params: bool, types: &mut Vec<(Position, Type)>) {
+    for entry in split_entries(tokens) {
+        match entry.as_slice() {
+            // `body = User`, `("id" = u64, Path)` in `params(...)`
+            [name, TokenTree::Punct(eq), rest @ ..] if eq.as_char() == '=' => {
+                let Some(position) = position(name, params) else {
+                    continue;
+                };
+                let tokens = match rest {
+                    // `body = inline(User)`
+                    [TokenTree::Ident(inline), TokenTree::Group(group)] if inline == "inline" => group.stream(),
+                    _ => rest.iter().cloned().collect(),
+                };
+                if let Ok(ty) = syn::parse2::<Type>(tokens) {
+                    types.push((position, ty));
+                }
+            }
+            // `responses(...)`, `request_body(content = User)`, `params(...)`
+            [TokenTree::Ident(name), TokenTree::Group(group)] if group.delimiter() == Delimiter::Parenthesis => {
+                collect_types(&group.stream(), name == "params", types)
+            }
+            // `(status = 200, body = User)`, `("id" = u64, Path)`
+            [TokenTree::Group(group)] if group.delimiter() == Delimiter::Parenthesis => {
+                collect_types(&group.stream(), params, types)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The position of the type following `name =`, if it is a type
+fn position(name: &TokenTree, params: bool) -> Option<Position> {
+    match name {
+        TokenTree::Ident(name) if name == "response" => Some(Position::Response),
+        TokenTree::Ident(name) if TYPE_ARGUMENTS.contains(&name.to_string().as_str()) => Some(Position::Body),
+        TokenTree::Literal(_) if params => Some(Position::Param),
+        _ => None,
+    }
+}
+
+/// The paths in `ty`, containers and primitives excluded, with the generic arguments of each
+fn collect_references(ty: &Type, position: Position, imports: &Imports, references: &mut Vec<TypeReference>) {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let Some(last) = path.path.segments.last() else {
+                return;
+            };
+            let name = last.ident.to_string();
+            if !CONTAINERS.contains(&name.as_str()) && !PRIMITIVES.contains(&name.as_str()) {
+                let mut written = path.path.clone();
+                written
+                    .segments
+                    .iter_mut()
+                    .for_each(|segment| segment.arguments = PathArguments::None);
+                references.push(TypeReference {
+                    position,
+                    name: written.to_token_stream().to_string().replace(' ', ""),
+                    candidates: imports.resolve(&written),
+                });
+            }
+            if let PathArguments::AngleBracketed(arguments) = &last.arguments {
+                for argument in &arguments.args {
+                    if let GenericArgument::Type(ty) = argument {
+                        collect_references(ty, position, imports, references);
+                    }
+                }
+            }
+        }
+        Type::Slice(slice) => collect_references(&slice.elem, position, imports, references),
+        Type::Array(array) => collect_references(&array.elem, position, imports, references),
+        Type::Reference(reference) => collect_references(&reference.elem, position, imports, references),
+        Type::Paren(paren) => collect_references(&paren.elem, position, imports, references),
+        Type::Group(group) => collect_references(&group.elem, position, imports, references),
+        Type::Tuple(tuple) => tuple
+            .elems
+            .iter()
+            .for_each(|ty| collect_references(ty, position, imports, references)),
+        _ => {}
+    }
+}
+
+/// Split the tokens of an argument list on its top level commas, the ones of `HashMap<K, V>` excluded
+fn split_entries(tokens: &TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut entries = vec![vec![]];
+    let mut depth = 0usize;
+    for tree in tokens.clone() {
+        match &tree {
+            TokenTree::Punct(punct) if punct.as_char() == ',' && depth == 0 => {
+                entries.push(vec![]);
+                continue;
+            }
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            TokenTree::Punct(punct) if punct.as_char() == '>' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        entries.last_mut().expect("there is always an entry").push(tree);
+    }
+    entries.retain(|entry| !entry.is_empty());
+    entries
+}
+
+#[cfg(test)]
+mod test {
+    use super::{handler_references, selected_items, unresolved_references_warnings, Imports, Position, Selection};
+    use crate::builder::{parse_registry, render_registry};
+    use crate::discover::{DiscoveredKind, Discoverer};
+
+    #[test]
+    fn test_handler_references() {
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[utoipa::path(
+                post,
+                path = "/users/{id}",
+                request_body(content = inline(CreateUser), content_type = "application/json"),
+                params(("id" = u64, Path, description = "The user id"), UserQuery),
+                responses(
+                    (status = 200, body = [User]),
+                    (status = 404, response = NotFound),
+                    (status = 500, body = Option<HashMap<String, errors::ApiError>>)
+                )
+            )]
+            pub fn update_user() {}
+        };
+        let imports = Imports::new(&syn::parse_quote!(crate::api), &[]);
+        let references = handler_references(&item.attrs, "utoipa", &imports)
+            .into_iter()
+            .map(|reference| (reference.position, reference.name))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            references,
+            vec![
+                (Position::Body, "CreateUser".to_string()),
+                (Position::Body, "User".to_string()),
+                (Position::Response, "NotFound".to_string()),
+                (Position::Body, "errors::ApiError".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_imports_resolve() {
+        let file: syn::File = syn::parse_quote! {
+            use crate::models::{self, User, Address as Location};
+            use super::errors::*;
+            use chrono::DateTime;
+        };
+        let imports = Imports::new(&syn::parse_quote!(crate::api::users), &file.items);
+        let resolve = |path: syn::Path| imports.resolve(&path);
+
+        assert_eq!(resolve(syn::parse_quote!(User)), vec!["crate::models::User"]);
+        assert_eq!(resolve(syn::parse_quote!(Location)), vec!["crate::models::Address"]);
+        assert_eq!(resolve(syn::parse_quote!(models::Page)), vec!["crate::models::Page"]);
+        assert_eq!(
+            resolve(syn::parse_quote!(ApiError)),
+            vec!["crate::api::users::ApiError", "crate::api::errors::ApiError"]
+        );
+        assert_eq!(
+            resolve(syn::parse_quote!(dto::UserDto)),
+            vec![
+                "crate::api::users::dto::UserDto",
+                "dto::UserDto",
+                "crate::api::errors::dto::UserDto"
+            ]
+        );
+        assert_eq!(resolve(syn::parse_quote!(super::Error)), vec!["crate::api::Error"]);
+        assert_eq!(
+            resolve(syn::parse_quote!(self::Error)),
+            vec!["crate::api::users::Error"]
+        );
+        assert_eq!(resolve(syn::parse_quote!(crate::Error)), vec!["crate::Error"]);
+        assert_eq!(resolve(syn::parse_quote!(::time::Date)), vec!["time::Date"]);
+        // `chrono` may be a module of `crate::api::users` or a crate
+        assert_eq!(
+            resolve(syn::parse_quote!(DateTime)),
+            vec!["crate::api::users::chrono::DateTime", "chrono::DateTime"]
+        );
+    }
+
+    #[test]
+    fn test_unresolved_references_warnings() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(
+                post,
+                path = "/users",
+                request_body = UserDto,
+                responses((status = 200, body = Page<User>), (status = 400, body = other::User))
+            )]
+            pub fn create_user() {}
+
+            #[derive(utoipa::ToSchema)]
+            pub struct User;
+
+            #[derive(utoipa::ToSchema)]
+            pub struct Page<T>(T);
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let warnings = unresolved_references_warnings(&items, &[]).to_string();
+        assert!(warnings.contains(
+            "utoipauto: the type `UserDto` referenced by `crate::create_user` (./src/lib.rs) is not a discovered \
+             schema or response, derive `ToSchema` for it or add it to `known_types`"
+        ));
+        assert!(!warnings.contains("`User`"));
+        // The generic schemas are skipped, they are not documented
+        assert!(warnings.contains("`Page`"));
+        // Another type with the same name
+        assert!(warnings.contains("`other::User`"));
+
+        let known = [
+            "UserDto".to_string(),
+            "Page".to_string(),
+            "crate::other::User".to_string(),
+        ];
+        assert!(unresolved_references_warnings(&items, &known).is_empty());
+        let known = [
+            "UserDto".to_string(),
+            "Page".to_string(),
+            "crate::dto::User".to_string(),
+        ];
+        assert!(unresolved_references_warnings(&items, &known)
+            .to_string()
+            .contains("`other::User`"));
+    }
+
+    #[test]
+    fn test_unresolved_references_warnings_registry() {
+        let file: syn::File = syn::parse_quote! {
+            use crate::models::User;
+
+            #[utoipa::path(get, path = "/users", responses((status = 200, body = User), (status = 400, body = ApiError)))]
+            pub fn list_users() {}
+
+            pub mod models {
+                #[derive(utoipa::ToSchema)]
+                pub struct User;
+            }
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = parse_registry(&render_registry(&items)).unwrap();
+        let warnings = unresolved_references_warnings(&items, &[]).to_string();
+        assert!(warnings.contains("`ApiError`"));
+        assert!(!warnings.contains("`User`"));
+    }
+
+    #[test]
+    fn test_selected_items() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(
+                get,
+                path = "/users/{id}",
+                params(("id" = UserId, Path), ("filter" = Filter, Query)),
+                responses((status = 200, body = User), (status = 404, response = NotFound))
+            )]
+            pub fn get_user() {}
+
+            #[derive(utoipa::ToSchema)]
+            pub struct User {
+                id: UserId,
+                #[schema(value_type = Address)]
+                address: String,
+            }
+
+            #[derive(utoipa::ToSchema)]
+            pub struct UserId(u64);
+
+            #[derive(utoipa::ToSchema)]
+            pub struct Address;
+
+            #[derive(utoipa::ToSchema)]
+            pub enum Filter {
+                Country(Country),
+            }
+
+            #[derive(utoipa::ToSchema)]
+            pub struct Country;
+
+            #[derive(utoipa::ToSchema)]
+            pub struct ErrorBody;
+
+            #[derive(utoipa::ToSchema)]
+            pub struct InternalDto;
+
+            #[derive(utoipa::ToResponse)]
+            pub struct NotFound {
+                error: ErrorBody,
+            }
+
+            #[derive(utoipa::ToResponse)]
+            pub struct Unused;
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        let selected = |schemas, responses, kind| {
+            selected_items(&items, schemas, responses)
+                .iter()
+                .filter(|item| item.kind == kind)
+                .map(|item| item.path_string())
+                .collect::<Vec<_>>()
+        };
+
+        // `User`, `UserId` and `Address` are collected by utoipa, `Country` with `Filter`
+        assert_eq!(
+            selected(Selection::Referenced, Selection::Referenced, DiscoveredKind::Schema),
+            vec!["crate::Filter", "crate::ErrorBody"]
+        );
+        assert_eq!(
+            selected(Selection::Referenced, Selection::Referenced, DiscoveredKind::Response),
+            vec!["crate::NotFound"]
+        );
+        assert_eq!(
+            selected(Selection::None, Selection::All, DiscoveredKind::Response),
+            vec!["crate::NotFound", "crate::Unused"]
+        );
+        assert!(selected(Selection::None, Selection::All, DiscoveredKind::Schema).is_empty());
+        assert_eq!(
+            selected(Selection::All, Selection::None, DiscoveredKind::Schema).len(),
+            7
+        );
+        assert_eq!(
+            selected(Selection::None, Selection::None, DiscoveredKind::Path),
+            vec!["crate::get_user"]
+        );
+    }
+
+    #[test]
+    fn test_selected_items_registry() {
+        let file: syn::File = syn::parse_quote! {
+            use crate::models::{Filter, NotFound};
+
+            #[utoipa::path(
+                get,
+                path = "/users",
+                params(("filter" = Filter, Query)),
+                responses((status = 200, body = models::User), (status = 404, response = NotFound))
+            )]
+            pub fn list_users() {}
+
+            pub mod models {
+                #[derive(utoipa::ToSchema)]
+                pub struct User;
+
+                #[derive(utoipa::ToSchema)]
+                pub struct Filter;
+
+                #[derive(utoipa::ToSchema)]
+                pub struct ErrorBody;
+
+                #[derive(utoipa::ToSchema)]
+                pub struct InternalDto;
+
+                #[derive(utoipa::ToResponse)]
+                pub struct NotFound {
+                    error: ErrorBody,
+                }
+            }
+        };
+        // As with `include`, from the registry written by the build script
+        let discovered = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = parse_registry(&render_registry(&discovered)).unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        let selected = selected_items(&items, Selection::Referenced, Selection::Referenced)
+            .iter()
+            .filter(|item| item.kind != DiscoveredKind::Path)
+            .map(|item| item.path_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            selected,
+            vec![
+                "crate::models::Filter",
+                "crate::models::ErrorBody",
+                "crate::models::NotFound"
+            ]
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! `#[utoipauto::register]`: register a handler, a schema or a response in a link time registry
+//!
+//! The items are collected with `inventory` and merged into the documentation at runtime by
+//! `#[utoipauto(discovery = "registry")]`, without reading the source files.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{punctuated::Punctuated, Attribute, Ident, Item, Token};
+
+/// The kinds of registration accepted by `#[register(...)]`
+const KINDS: &[&str] = &["schema", "response"];
+
+/// The item followed by its registrations
+pub fn register(attributes: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    if !cfg!(feature = "registry") {
+        return Err(syn::Error::new_spanned(
+            &item,
+            "utoipauto: `#[register]` requires the `registry` feature of utoipauto",
+        ));
+    }
+    let kinds = syn::parse::Parser::parse2(Punctuated::<Ident, Token![,]>::parse_terminated, attributes)?;
+    for kind in &kinds {
+        if !KINDS.contains(&kind.to_string().as_str()) {
+            return Err(syn::Error::new(
+                kind.span(),
+                format!("utoipauto: unknown registration `{kind}`, expected `schema` or `response`"),
+            ));
+        }
+    }
+    let explicit = |name: &str| kinds.iter().any(|kind| kind == name);
+
+    let parsed: Item = syn::parse2(item.clone())?;
+    let registrations = match &parsed {
+        Item::Fn(function) => {
+            if let Some(kind) = kinds.first() {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    "utoipauto: a handler is registered as a path, without arguments",
+                ));
+            }
+            // A documentation of the handler alone, utoipa collects the schemas it references
+            let ident = &function.sig.ident;
+            let openapi = format_ident!("__utoipauto_path_{}", ident);
+            vec![
+                quote!(
+                    #[doc(hidden)]
+                    #[allow(non_camel_case_types)]
+                    #[derive(::utoipa::OpenApi)]
+                    #[openapi(paths(#ident))]
+                    struct #openapi;
+                ),
+                registration(ident, quote!(path::<#openapi>)),
+            ]
+        }
+        Item::Struct(syn::ItemStruct {
+            ident, generics, attrs, ..
+        })
+        | Item::Enum(syn::ItemEnum {
+            ident, generics, attrs, ..
+        }) => {
+            if !generics.params.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    generics,
+                    "utoipauto: generic types can't be registered, only their concrete uses can be documented",
+                ));
+            }
+            let (schema, response) = match kinds.is_empty() {
+                true => (derives(attrs, "ToSchema"), derives(attrs, "ToResponse")),
+                false => (explicit("schema"), explicit("response")),
+            };
+            if !schema && !response {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "utoipauto: no `ToSchema` or `ToResponse` derive found, \
+                     use `#[register(schema)]` or `#[register(response)]` for a manual implementation",
+                ));
+            }
+            let mut registrations = vec![];
+            if schema {
+                registrations.push(registration(ident, quote!(schema::<#ident>)));
+            }
+            if response {
+                registrations.push(registration(ident, quote!(response::<#ident>)));
+            }
+            registrations
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &item,
+                "utoipauto: `#[register]` goes on a handler, a struct or an enum",
+            ))
+        }
+    };
+
+    Ok(quote!(
+        #item
+        #(#registrations)*
+    ))
+}
+
+/// `inventory::submit!` of a registration named after the module path of the item
+fn registration(ident: &Ident, constructor: TokenStream) -> TokenStream {
+    let name = ident.to_string();
+    quote!(::utoipauto::registry::inventory::submit! {
+        ::utoipauto::registry::Registration::#constructor(::core::concat!(::core::module_path!(), "::", #name))
+    })
+}
+
+/// Whether the item derives `name`, with or without a path
+fn derives(attrs: &[Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .any(|path| path.segments.last().is_some_and(|segment| segment.ident == name))
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+
+    use super::register;
+
+    #[test]
+    fn test_register() {
+        let handler = register(
+            quote!(),
+            quote!(
+                pub fn get_user() {}
+            ),
+        );
+        if !cfg!(feature = "registry") {
+            assert_eq!(
+                handler.err().unwrap().to_string(),
+                "utoipauto: `#[register]` requires the `registry` feature of utoipauto"
+            );
+            return;
+        }
+        let handler = handler.unwrap().to_string();
+        assert!(handler.contains("# [openapi (paths (get_user))] struct __utoipauto_path_get_user ;"));
+        assert!(handler.contains("Registration :: path :: < __utoipauto_path_get_user >"));
+
+        let user = register(
+            quote!(),
+            quote!(
+                #[derive(utoipa::ToSchema, ToResponse)]
+                struct User;
+            ),
+        )
+        .unwrap()
+        .to_string();
+        assert!(user.contains("Registration :: schema :: < User >"));
+        assert!(user.contains("Registration :: response :: < User >"));
+
+        let manual = register(
+            quote!(response),
+            quote!(
+                #[derive(ToSchema)]
+                struct NotFound;
+            ),
+        )
+        .unwrap()
+        .to_string();
+        assert!(!manual.contains("Registration :: schema"));
+        assert!(manual.contains("Registration :: response :: < NotFound >"));
+    }
+
+    #[test]
+    fn test_register_invalid() {
+        if !cfg!(feature = "registry") {
+            return;
+        }
+        assert!(register(
+            quote!(),
+            quote!(
+                struct User;
+            )
+        )
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("no `ToSchema` or `ToResponse` derive found"));
+        assert!(register(
+            quote!(),
+            quote!(
+                #[derive(ToSchema)]
+                struct Page<T>(T);
+            )
+        )
+        .is_err());
+        assert!(register(
+            quote!(schema),
+            quote!(
+                fn get_user() {}
+            )
+        )
+        .is_err());
+        assert!(register(
+            quote!(model),
+            quote!(
+                #[derive(ToSchema)]
+                struct User;
+            )
+        )
+        .is_err());
+        assert!(register(
+            quote!(),
+            quote!(
+                const USER: u8 = 0;
+            )
+        )
+        .is_err());
+    }
+}
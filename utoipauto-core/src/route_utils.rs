@@ -0,0 +1,419 @@
+use proc_macro2::{TokenStream, TokenTree};
+use syn::{Attribute, Lit, LitStr, Meta};
+
+use crate::discover::{DiscoveredItem, DiscoveredKind};
+use crate::token_utils::{combine_error, Prefix};
+
+/// The HTTP methods accepted by `#[utoipa::path(...)]` and by the route attributes of the web frameworks
+pub const HTTP_METHODS: &[&str] = &[
+    "get", "post", "put", "delete", "patch", "head", "options", "trace", "connect",
+];
+
+/// The route of a handler, parsed from its `#[utoipa::path(...)]` attribute,
+/// or from the route attribute of its web framework: `#[get("/users")]`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Route {
+    /// The HTTP methods, in lower case: `get`
+    pub methods: Vec<String>,
+    /// `/users/{id}`
+    pub path: Option<String>,
+    /// `context_path = "/api"`, prepended to `path` by utoipa
+    pub context_path: Option<String>,
+    /// `path` or `context_path` is an expression instead of a string literal, the full path isn't known
+    pub expression_path: bool,
+    /// `operation_id = "..."`, if it is a string literal
+    pub operation_id: Option<String>,
+    /// `operation_id = LIST_USERS`: the operation id is an expression, it isn't known
+    pub expression_operation_id: bool,
+    /// The path of the route attribute of the web framework, as written: `get`, `actix_web::post`
+    pub framework: Option<String>,
+    /// `tag = ...` or `tags = [...]` is set by `#[utoipa::path(...)]`
+    pub tagged: bool,
+}
+
+impl Route {
+    /// Parse the route of a handler from its attributes
+    ///
+    /// The `#[utoipa::path(...)]` attribute wins, a framework attribute fills what it doesn't declare
+    pub fn from_attributes(attrs: &[Attribute], fn_attribute_name: &str) -> Option<Route> {
+        let mut route = attrs
+            .iter()
+            .filter(|attr| attr.path().segments.iter().any(|s| s.ident == fn_attribute_name))
+            .find_map(|attr| {
+                attr.meta
+                    .require_list()
+                    .ok()
+                    .map(|list| parse_path_attribute(&list.tokens))
+            })
+            .unwrap_or_default();
+        if let Some(framework) = attrs.iter().find_map(framework_route) {
+            if route.methods.is_empty() {
+                route.methods = framework.methods;
+            }
+            if route.path.is_none() {
+                route.path = framework.path;
+            }
+            route.framework = framework.framework;
+        }
+        (route != Route::default()).then_some(route)
+    }
+
+    /// The path documented by utoipa, `context_path` included: `/api/users/{id}`
+    ///
+    /// `None` if the path is missing or isn't made of string literals
+    pub fn full_path(&self) -> Option<String> {
+        if self.expression_path {
+            return None;
+        }
+        let path = self.path.as_ref()?;
+        Some(format!("{}{}", self.context_path.as_deref().unwrap_or_default(), path))
+    }
+}
+
+/// `#[utoipa::path(get, path = "/users", operation_id = "list_users", ...)]`
+///
+/// Only the top level entries are read, so the attribute doesn't need to be valid `syn::Meta`
+fn parse_path_attribute(tokens: &TokenStream) -> Route {
+    let mut route = Route::default();
+    for entry in split_entries(tokens) {
+        match entry.as_slice() {
+            [TokenTree::Ident(method)] if HTTP_METHODS.contains(&method.to_string().as_str()) => {
+                route.methods.push(method.to_string())
+            }
+            // utoipa 5: `method(get, head)`
+            [TokenTree::Ident(name), TokenTree::Group(group)] if name == "method" => {
+                route
+                    .methods
+                    .extend(group.stream().into_iter().filter_map(|tree| match tree {
+                        TokenTree::Ident(method) => Some(method.to_string().to_lowercase()),
+                        _ => None,
+                    }))
+            }
+            [TokenTree::Ident(name), TokenTree::Punct(eq), ..]
+                if eq.as_char() == '=' && (name == "tag" || name == "tags") =>
+            {
+                route.tagged = true
+            }
+            [TokenTree::Ident(name), TokenTree::Punct(eq), rest @ ..] if eq.as_char() == '=' => {
+                let value = match rest {
+                    [TokenTree::Literal(lit)] => match Lit::new(lit.clone()) {
+                        Lit::Str(value) => Some(value.value()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match (name.to_string().as_str(), value) {
+                    ("path", Some(value)) => route.path = Some(value),
+                    ("context_path", Some(value)) => route.context_path = Some(value),
+                    ("operation_id", Some(value)) => route.operation_id = Some(value),
+                    // `path = USERS_PATH`, `context_path = concat!(...)`
+                    ("path" | "context_path", None) => route.expression_path = true,
+                    ("operation_id", None) => route.expression_operation_id = true,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    route
+}
+
+/// The route attribute of a web framework: `#[get("/users")]`, `#[actix_web::post("/users")]`
+fn framework_route(attr: &Attribute) -> Option<Route> {
+    let Meta::List(list) = &attr.meta else { return None };
+    let method = list.path.segments.last()?.ident.to_string();
+    if !HTTP_METHODS.contains(&method.as_str()) {
+        return None;
+    }
+    let path = split_entries(&list.tokens)
+        .into_iter()
+        .next()
+        .and_then(|entry| match entry.as_slice() {
+            [TokenTree::Literal(lit)] => syn::parse2::<LitStr>(TokenTree::Literal(lit.clone()).into())
+                .ok()
+                .map(|lit| lit.value()),
+            _ => None,
+        })?;
+    let framework = list
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::");
+    Some(Route {
+        methods: vec![method],
+        path: Some(path),
+        framework: Some(framework),
+        ..Route::default()
+    })
+}
+
+/// An error for each method and path documented by two discovered handlers, with the location of both
+///
+/// The paths are compared with their `context_path` and their module prefix, and without the names of their
+/// parameters: `/users/{id}` and `/users/{user_id}` are the same route. The handlers whose path isn't made of string
+/// literals are not checked, nor the handlers whose cfg predicates can't be enabled together.
+pub fn check_routes(items: &[&DiscoveredItem], prefixes: &[Prefix]) -> syn::Result<()> {
+    let mut errors: Option<syn::Error> = None;
+    let mut seen: Vec<(String, String, String, &DiscoveredItem)> = Vec::new();
+    let handlers = items
+        .iter()
+        .copied()
+        .filter(|item| item.kind == DiscoveredKind::Path && item.is_discovered());
+    for item in handlers {
+        let Some(route) = &item.route else {
+            continue;
+        };
+        let Some(path) = route.full_path() else {
+            continue;
+        };
+        let path = match Prefix::find(prefixes, &item.path_string()) {
+            Some(prefix) if path == "/" => prefix.prefix.clone(),
+            Some(prefix) => format!("{}{}", prefix.prefix, path),
+            None => path,
+        };
+        let normalized = normalize_path(&path);
+        for method in &route.methods {
+            let method = method.to_uppercase();
+            let duplicate = seen.iter().find(|(other_method, other, _, first)| {
+                *other_method == method && *other == normalized && item.compiled_with(first)
+            });
+            match duplicate {
+                Some((_, _, first_path, first)) => combine_error(
+                    &mut errors,
+                    syn::Error::new(
+                        item.span,
+                        format!(
+                            "utoipauto: the route `{} {}` of `{}` ({}) is already documented by `{} {}` of `{}` ({})",
+                            method,
+                            path,
+                            item.path_string(),
+                            item.location(),
+                            method,
+                            first_path,
+                            first.path_string(),
+                            first.location()
+                        ),
+                    ),
+                ),
+                None => seen.push((method, normalized.clone(), path.clone(), item)),
+            }
+        }
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+/// The path without the names of its parameters: `/users/{}` for `/users/{id}`, `/users/<id>` or `/users/:id`
+fn normalize_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            let parameter = (segment.starts_with('{') && segment.ends_with('}'))
+                || (segment.starts_with('<') && segment.ends_with('>'))
+                || segment.starts_with(':');
+            match parameter {
+                true => "{}",
+                false => segment,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Split the tokens of an attribute on its top level commas
+fn split_entries(tokens: &TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut entries = vec![vec![]];
+    for tree in tokens.clone() {
+        match &tree {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => entries.push(vec![]),
+            _ => entries.last_mut().expect("there is always an entry").push(tree),
+        }
+    }
+    entries.retain(|entry| !entry.is_empty());
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_routes, Route};
+    use crate::discover::Discoverer;
+    use crate::token_utils::Prefix;
+
+    #[test]
+    fn test_duplicate_routes() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/users/{id}")]
+            pub fn get_user() {}
+
+            #[utoipa::path(delete, path = "/users/{id}")]
+            pub fn delete_user() {}
+
+            #[utoipa::path(get, path = "/users/{user_id}")]
+            pub fn find_user() {}
+
+            mod v1 {
+                #[utoipa::path(get, path = "/users/{id}")]
+                pub fn get_user() {}
+            }
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        let error = check_routes(&items, &[]).unwrap_err();
+        let messages = error.into_iter().map(|error| error.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "utoipauto: the route `GET /users/{user_id}` of `crate::find_user` (./src/lib.rs) is already \
+                 documented by `GET /users/{id}` of `crate::get_user` (./src/lib.rs)",
+                "utoipauto: the route `GET /users/{id}` of `crate::v1::get_user` (./src/lib.rs) is already \
+                 documented by `GET /users/{id}` of `crate::get_user` (./src/lib.rs)",
+            ]
+        );
+
+        // The prefix of the module tells the routes apart
+        let prefixes = [Prefix {
+            module: "crate::v1".to_string(),
+            prefix: "/api/v1".to_string(),
+        }];
+        let error = check_routes(&items, &prefixes).unwrap_err();
+        assert_eq!(error.into_iter().count(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_routes_cfg() {
+        let file: syn::File = syn::parse_quote! {
+            #[cfg(feature = "fast")]
+            #[utoipa::path(get, path = "/status")]
+            pub fn status() {}
+
+            #[cfg(not(feature = "fast"))]
+            #[utoipa::path(get, path = "/status")]
+            pub fn status() {}
+
+            #[cfg(feature = "fast")]
+            #[utoipa::path(get, path = "/status")]
+            pub fn fast_status() {}
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        // Only one of the first two can be compiled, the third one is compiled with the first
+        assert!(check_routes(&items[..2], &[]).is_ok());
+        let error = check_routes(&items, &[]).unwrap_err();
+        let messages = error.into_iter().map(|error| error.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "utoipauto: the route `GET /status` of `crate::fast_status` (./src/lib.rs) is already documented \
+                 by `GET /status` of `crate::status` (./src/lib.rs)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_routes_context_path() {
+        let file: syn::File = syn::parse_quote! {
+            mod a {
+                #[utoipa::path(get, path = "/users", context_path = "/a")]
+                pub fn x() {}
+            }
+
+            mod b {
+                #[utoipa::path(get, path = "/users", context_path = "/b")]
+                pub fn y() {}
+
+                #[utoipa::path(get, path = "/users", context_path = B_CONTEXT)]
+                pub fn z() {}
+
+                #[utoipa::path(get, path = USERS_PATH)]
+                pub fn w() {}
+            }
+
+            #[utoipa::path(get, path = "/a/users")]
+            pub fn v() {}
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        let error = check_routes(&items, &[]).unwrap_err();
+        let messages = error.into_iter().map(|error| error.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "utoipauto: the route `GET /a/users` of `crate::v` (./src/lib.rs) is already documented by \
+                 `GET /a/users` of `crate::a::x` (./src/lib.rs)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_route_from_utoipa_path() {
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[utoipa::path(
+                get,
+                path = "/users/{id}",
+                operation_id = "get_user",
+                responses((status = 200, body = User))
+            )]
+            async fn get_user() {}
+        };
+        let route = Route::from_attributes(&item.attrs, "utoipa").unwrap();
+        assert_eq!(route.methods, vec!["get"]);
+        assert_eq!(route.path.as_deref(), Some("/users/{id}"));
+        assert_eq!(route.operation_id.as_deref(), Some("get_user"));
+        assert!(!route.tagged);
+
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[utoipa::path(get, path = "/users", context_path = "/api")]
+            async fn list_users() {}
+        };
+        let route = Route::from_attributes(&item.attrs, "utoipa").unwrap();
+        assert_eq!(route.full_path().as_deref(), Some("/api/users"));
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[utoipa::path(get, path = "/users", context_path = API_CONTEXT)]
+            async fn list_users() {}
+        };
+        assert_eq!(Route::from_attributes(&item.attrs, "utoipa").unwrap().full_path(), None);
+
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[utoipa::path(get, path = "/users", operation_id = LIST_USERS)]
+            async fn list_users() {}
+        };
+        let route = Route::from_attributes(&item.attrs, "utoipa").unwrap();
+        assert_eq!(route.operation_id, None);
+        assert!(route.expression_operation_id);
+
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[utoipa::path(get, path = "/users", tags = ["users", USERS_TAG])]
+            async fn list_users() {}
+        };
+        assert!(Route::from_attributes(&item.attrs, "utoipa").unwrap().tagged);
+    }
+
+    #[test]
+    fn test_route_from_method_list() {
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[utoipa::path(method(get, head), path = "/health")]
+            async fn health() {}
+        };
+        let route = Route::from_attributes(&item.attrs, "utoipa").unwrap();
+        assert_eq!(route.methods, vec!["get", "head"]);
+    }
+
+    #[test]
+    fn test_route_from_framework_attribute() {
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[utoipa::path(responses((status = 200)))]
+            #[actix_web::post("/users")]
+            async fn create_user() {}
+        };
+        let route = Route::from_attributes(&item.attrs, "utoipa").unwrap();
+        assert_eq!(route.methods, vec!["post"]);
+        assert_eq!(route.path.as_deref(), Some("/users"));
+        assert_eq!(route.framework.as_deref(), Some("actix_web::post"));
+
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[handler]
+            async fn handler() {}
+        };
+        assert!(Route::from_attributes(&item.attrs, "handler").is_none());
+    }
+}
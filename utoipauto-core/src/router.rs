@@ -0,0 +1,326 @@
+//! Register the discovered handlers with the router of a web framework
+//!
+//! `#[utoipauto(router = "axum")]` generates the routing from the same discovery as the documentation,
+//! so that the two can't drift.
+//!
+//! `prefixes = { "crate::api::v1" = "/api/v1" }` mounts the handlers of a module under a route prefix, which is also
+//! added to their documented paths.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+use crate::discover::{cfg_groups, DiscoveredItem, DiscoveredKind};
+use crate::route_utils::Route;
+use crate::token_utils::Prefix;
+
+/// The web frameworks a router can be generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouterKind {
+    /// `pub fn router<S>() -> utoipa_axum::router::OpenApiRouter<S>`
+    Axum,
+    /// `pub fn configure(cfg: &mut actix_web::web::ServiceConfig)`
+    Actix,
+    /// `pub fn routes() -> Vec<rocket::Route>` and `pub fn mount(rocket)`
+    Rocket,
+}
+
+impl RouterKind {
+    /// The values of `router = "..."`, with the cargo feature they require
+    pub const CHOICES: &'static [(&'static str, RouterKind, bool)] = &[
+        ("axum", RouterKind::Axum, cfg!(feature = "axum")),
+        ("actix", RouterKind::Actix, cfg!(feature = "actix")),
+        ("rocket", RouterKind::Rocket, cfg!(feature = "rocket")),
+    ];
+
+    /// Parse `router = "..."`, the framework must be enabled with its cargo feature
+    pub fn parse(value: &str, span: Span) -> syn::Result<RouterKind> {
+        match Self::CHOICES.iter().find(|(name, _, _)| *name == value) {
+            Some((_, kind, true)) => Ok(*kind),
+            Some((name, _, false)) => Err(syn::Error::new(
+                span,
+                format!("utoipauto: `router = \"{name}\"` requires the `{name}` feature of utoipauto"),
+            )),
+            None => Err(syn::Error::new(span, format!("utoipauto: unknown router \"{value}\""))),
+        }
+    }
+
+    /// The names accepted by `router = "..."`
+    pub fn names() -> Vec<&'static str> {
+        Self::CHOICES.iter().map(|(name, _, _)| *name).collect()
+    }
+}
+
+/// The impl block registering the discovered handlers with the router of `kind`
+pub fn router(
+    openapi_macro: &syn::ItemStruct,
+    kind: RouterKind,
+    items: &[&DiscoveredItem],
+    prefixes: &[Prefix],
+) -> TokenStream {
+    let handlers = items
+        .iter()
+        .filter(|item| item.kind == DiscoveredKind::Path && item.is_discovered())
+        .copied()
+        .collect::<Vec<_>>();
+    let function = match kind {
+        RouterKind::Axum => axum_router(&handlers, prefixes),
+        RouterKind::Actix => actix_configure(&handlers, prefixes),
+        RouterKind::Rocket => rocket_routes(&handlers, prefixes),
+    };
+
+    let ident = &openapi_macro.ident;
+    let (impl_generics, ty_generics, where_clause) = openapi_macro.generics.split_for_impl();
+    quote!(
+        #[allow(dead_code)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #function
+        }
+    )
+}
+
+/// `routes!(...)` for each route path, with every handler of the path
+fn axum_router(handlers: &[&DiscoveredItem], prefixes: &[Prefix]) -> TokenStream {
+    let routes = |handlers: &[&DiscoveredItem]| {
+        let routes = by_cfg(handlers).into_iter().flat_map(|(cfg, handlers)| {
+            group_by_route(&handlers).into_iter().map(move |handlers| {
+                let handlers = handlers.iter().map(|item| &item.path);
+                quote!(#cfg let router = router.routes(::utoipa_axum::routes!(#(#handlers),*));)
+            })
+        });
+        quote!(#(#routes)*)
+    };
+    let groups = group_by_prefix(handlers, prefixes)
+        .into_iter()
+        .map(|(prefix, handlers)| match prefix {
+            Some(prefix) => {
+                let routes = routes(&handlers);
+                quote!(let router = router.nest(#prefix, {
+                    let router = ::utoipa_axum::router::OpenApiRouter::new();
+                    #routes
+                    router
+                });)
+            }
+            None => routes(&handlers),
+        });
+    quote!(
+        /// An `OpenApiRouter` with the documentation and every handler found by `#[utoipauto]`,
+        /// the handlers sharing a path are registered together
+        pub fn router<S>() -> ::utoipa_axum::router::OpenApiRouter<S>
+        where
+            S: ::std::clone::Clone + ::std::marker::Send + ::std::marker::Sync + 'static,
+        {
+            let router = ::utoipa_axum::router::OpenApiRouter::with_openapi(<Self as ::utoipa::OpenApi>::openapi());
+            #(#groups)*
+            router
+        }
+    )
+}
+
+/// `cfg.service(...)` for each handler with a route macro, the handlers of a prefixed module in a scope
+fn actix_configure(handlers: &[&DiscoveredItem], prefixes: &[Prefix]) -> TokenStream {
+    let handlers = with_route_macro(handlers);
+    let services = group_by_prefix(&handlers, prefixes)
+        .into_iter()
+        .map(|(prefix, handlers)| {
+            let groups = by_cfg(&handlers).into_iter().map(|(cfg, handlers)| {
+                let handlers = handlers.iter().map(|item| &item.path);
+                match prefix {
+                    Some(_) => quote!(#cfg let scope = scope #(.service(#handlers))*;),
+                    None => quote!(#(#cfg cfg.service(#handlers);)*),
+                }
+            });
+            match prefix {
+                Some(prefix) => quote!(cfg.service({
+                    let scope = ::actix_web::web::scope(#prefix);
+                    #(#groups)*
+                    scope
+                });),
+                None => quote!(#(#groups)*),
+            }
+        });
+    quote!(
+        /// Register every handler found by `#[utoipauto]`, for `App::configure`
+        pub fn configure(cfg: &mut ::actix_web::web::ServiceConfig) {
+            #(#services)*
+        }
+    )
+}
+
+/// `routes![...]` with every handler with a route attribute, and a `mount` function mounting the handlers of
+/// the prefixed modules at their prefix
+fn rocket_routes(handlers: &[&DiscoveredItem], prefixes: &[Prefix]) -> TokenStream {
+    let handlers = with_route_macro(handlers);
+    let routes = by_cfg(&handlers).into_iter().map(|(cfg, handlers)| {
+        let handlers = handlers.iter().map(|item| &item.path);
+        quote!(#cfg routes.extend(::rocket::routes![#(#handlers),*]);)
+    });
+    let mounts = group_by_prefix(&handlers, prefixes)
+        .into_iter()
+        .flat_map(|(prefix, handlers)| {
+            let prefix = prefix.unwrap_or("/");
+            by_cfg(&handlers).into_iter().map(move |(cfg, handlers)| {
+                let handlers = handlers.iter().map(|item| &item.path);
+                quote!(#cfg let rocket = rocket.mount(#prefix, ::rocket::routes![#(#handlers),*]);)
+            })
+        });
+    quote!(
+        /// Every handler found by `#[utoipauto]`, to mount at a single base
+        pub fn routes() -> ::std::vec::Vec<::rocket::Route> {
+            #[allow(unused_mut)]
+            let mut routes = ::std::vec::Vec::new();
+            #(#routes)*
+            routes
+        }
+
+        /// Mount every handler found by `#[utoipauto]`, at the prefix of its module or at `/`
+        pub fn mount(rocket: ::rocket::Rocket<::rocket::Build>) -> ::rocket::Rocket<::rocket::Build> {
+            #(#mounts)*
+            rocket
+        }
+    )
+}
+
+/// The handlers grouped by cfg predicate, with the `#[cfg(...)]` of the statements registering them
+fn by_cfg<'a>(handlers: &[&'a DiscoveredItem]) -> Vec<(Option<TokenStream>, Vec<&'a DiscoveredItem>)> {
+    cfg_groups(handlers.iter().copied())
+        .into_iter()
+        .filter(|(_, handlers)| !handlers.is_empty())
+        .map(|(cfg, handlers)| (cfg.map(|cfg| quote!(#[cfg(#cfg)])), handlers))
+        .collect()
+}
+
+/// The handlers registered by a route macro of the framework, like `#[get("/users")]`
+///
+/// The handlers only documented by `#[utoipa::path]` have nothing to register
+fn with_route_macro<'a>(handlers: &[&'a DiscoveredItem]) -> Vec<&'a DiscoveredItem> {
+    handlers
+        .iter()
+        .filter(|item| item.route.as_ref().is_some_and(|route| route.framework.is_some()))
+        .copied()
+        .collect()
+}
+
+/// Group the handlers by the route prefix of their module, in discovery order
+fn group_by_prefix<'a>(
+    handlers: &[&'a DiscoveredItem],
+    prefixes: &'a [Prefix],
+) -> Vec<(Option<&'a str>, Vec<&'a DiscoveredItem>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&DiscoveredItem>)> = vec![];
+    for handler in handlers {
+        let prefix = Prefix::find(prefixes, &handler.path_string()).map(|prefix| prefix.prefix.as_str());
+        match groups.iter_mut().find(|(group, _)| *group == prefix) {
+            Some((_, group)) => group.push(handler),
+            None => groups.push((prefix, vec![handler])),
+        }
+    }
+    groups
+}
+
+/// Group the handlers by route path, in discovery order
+///
+/// Handlers whose path, `context_path` included, isn't made of string literals can't be grouped, they get a group
+/// of their own
+fn group_by_route<'a>(handlers: &[&'a DiscoveredItem]) -> Vec<Vec<&'a DiscoveredItem>> {
+    let mut groups: Vec<(Option<String>, Vec<&DiscoveredItem>)> = vec![];
+    for handler in handlers {
+        let path = handler.route.as_ref().and_then(Route::full_path);
+        match groups.iter_mut().find(|(group, _)| path.is_some() && *group == path) {
+            Some((_, group)) => group.push(handler),
+            None => groups.push((path, vec![handler])),
+        }
+    }
+    groups.into_iter().map(|(_, group)| group).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{group_by_prefix, group_by_route};
+    use crate::discover::Discoverer;
+    use crate::token_utils::Prefix;
+
+    #[test]
+    fn test_group_by_route() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/users")]
+            pub fn list_users() {}
+
+            #[utoipa::path(get, path = "/users/{id}")]
+            pub fn get_user() {}
+
+            #[utoipa::path(post, path = "/users")]
+            pub fn create_user() {}
+
+            #[utoipa::path(get, path = USERS_PATH)]
+            pub fn legacy_users() {}
+        };
+        let items = Discoverer::new().file("./src/api.rs", file).discover().unwrap();
+        let handlers = items.iter().collect::<Vec<_>>();
+        let groups = group_by_route(&handlers)
+            .iter()
+            .map(|group| group.iter().map(|item| item.path_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            vec![
+                vec!["crate::api::list_users", "crate::api::create_user"],
+                vec!["crate::api::get_user"],
+                vec!["crate::api::legacy_users"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_prefix() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/health")]
+            #[get("/health")]
+            pub fn health() {}
+
+            pub mod v1 {
+                #[utoipa::path(get, path = "/users")]
+                #[get("/users")]
+                pub fn list_users() {}
+
+                pub mod admin {
+                    #[utoipa::path(delete, path = "/users/{id}")]
+                    #[delete("/users/{id}")]
+                    pub fn delete_user() {}
+                }
+            }
+
+            pub mod v1_legacy {
+                #[utoipa::path(get, path = "/users")]
+                #[get("/users")]
+                pub fn list_users() {}
+            }
+        };
+        let items = Discoverer::new().file("./src/lib.rs", file).discover().unwrap();
+        let handlers = items.iter().collect::<Vec<_>>();
+        let prefixes = vec![
+            Prefix {
+                module: "crate::v1".to_string(),
+                prefix: "/api/v1".to_string(),
+            },
+            Prefix {
+                module: "crate::v1::admin".to_string(),
+                prefix: "/admin".to_string(),
+            },
+        ];
+        let groups = group_by_prefix(&handlers, &prefixes)
+            .iter()
+            .map(|(prefix, group)| (*prefix, group.iter().map(|item| item.path_string()).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        let groups = groups
+            .iter()
+            .map(|(prefix, group)| (*prefix, group.iter().map(String::as_str).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            vec![
+                (None, vec!["crate::health", "crate::v1_legacy::list_users"]),
+                (Some("/api/v1"), vec!["crate::v1::list_users"]),
+                (Some("/admin"), vec!["crate::v1::admin::delete_user"]),
+            ]
+        );
+    }
+}
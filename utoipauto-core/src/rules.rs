@@ -0,0 +1,484 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, ItemEnum, ItemFn, ItemImpl, ItemStruct, Meta, Token};
+
+/// How a discovery rule classifies an item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classification {
+    /// A handler to add to `paths(...)`
+    Path,
+    /// A type to add to `components(schemas(...))`
+    Schema,
+    /// A type to add to `components(responses(...))`
+    Response,
+    /// A unit struct marked with `#[utoipauto::modifier]`, to add to `modifiers(...)`
+    Modifier,
+    /// A documentation struct marked with `#[utoipauto::nest]`, to add to `nest(...)`
+    Nest,
+    /// The item must be left out of the documentation, whatever the other rules say
+    Ignore,
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Classification::Path => write!(f, "path"),
+            Classification::Schema => write!(f, "schema"),
+            Classification::Response => write!(f, "response"),
+            Classification::Modifier => write!(f, "modifier"),
+            Classification::Nest => write!(f, "nested documentation"),
+            Classification::Ignore => write!(f, "ignored"),
+        }
+    }
+}
+
+/// An item given to the discovery rules
+#[derive(Clone, Copy)]
+pub enum RuleItem<'a> {
+    Fn(&'a ItemFn),
+    Struct(&'a ItemStruct),
+    Enum(&'a ItemEnum),
+    Impl(&'a ItemImpl),
+}
+
+impl RuleItem<'_> {
+    pub fn attrs(&self) -> &[Attribute] {
+        match self {
+            RuleItem::Fn(f) => &f.attrs,
+            RuleItem::Struct(s) => &s.attrs,
+            RuleItem::Enum(e) => &e.attrs,
+            RuleItem::Impl(im) => &im.attrs,
+        }
+    }
+
+    /// The paths of the derives of a struct or an enum: `Debug`, `utoipa::ToSchema`
+    pub fn derives(&self) -> syn::Result<Vec<syn::Path>> {
+        if matches!(self, RuleItem::Fn(_) | RuleItem::Impl(_)) {
+            return Ok(vec![]);
+        }
+        let mut derives = vec![];
+        for attr in self.attrs().iter().filter(|attr| attr.path().is_ident("derive")) {
+            let nested = attr
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .map_err(|e| {
+                    syn::Error::new(
+                        e.span(),
+                        format!("failed to parse derive attribute of `{}`: {}", self.name(), e),
+                    )
+                })?;
+            derives.extend(nested.into_iter().map(|meta| meta.path().clone()));
+        }
+        Ok(derives)
+    }
+
+    /// The path of the implemented trait: `ToSchema` for `impl ToSchema for Model`
+    pub fn trait_path(&self) -> Option<&syn::Path> {
+        match self {
+            RuleItem::Impl(im) => im.trait_.as_ref().map(|(_, path, _)| path),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            RuleItem::Fn(f) => f.sig.ident.to_string(),
+            RuleItem::Struct(s) => s.ident.to_string(),
+            RuleItem::Enum(e) => e.ident.to_string(),
+            RuleItem::Impl(im) => im.self_ty.to_token_stream().to_string(),
+        }
+    }
+}
+
+/// Decide what a function, a struct, an enum or an impl block documents
+///
+/// The discovery merges the classifications of every rule: an item can be both a schema and a response,
+/// and an item is left out as soon as one rule returns [`Classification::Ignore`].
+///
+/// ```
+/// use utoipauto_core::rules::{Classification, DiscoveryRule, RuleItem};
+///
+/// /// `#[derive(ApiModel)]` expands to `#[derive(ToSchema)]`
+/// struct ApiModelRule;
+///
+/// impl DiscoveryRule for ApiModelRule {
+///     fn name(&self) -> String {
+///         "ApiModel".to_string()
+///     }
+///
+///     fn classify(&self, item: &RuleItem<'_>) -> syn::Result<Vec<Classification>> {
+///         let derives = item.derives()?;
+///         Ok(match derives.iter().any(|path| path.is_ident("ApiModel")) {
+///             true => vec![Classification::Schema],
+///             false => vec![],
+///         })
+///     }
+/// }
+/// ```
+pub trait DiscoveryRule {
+    /// Describe the rule in the explanations of the `explain` option
+    fn name(&self) -> String;
+
+    /// The classifications of the item, empty if the rule doesn't apply to it
+    fn classify(&self, item: &RuleItem<'_>) -> syn::Result<Vec<Classification>>;
+}
+
+/// The default rule: `#[utoipa::path]` functions, `ToSchema` and `ToResponse` derives and impls,
+/// `#[utoipauto::modifier]` unit structs, `#[utoipauto::nest]` structs, and `#[utoipa_ignore]` items
+#[derive(Debug, Clone)]
+pub struct UtoipaRule {
+    pub fn_attribute_name: String,
+    pub schema_attribute_name: String,
+    pub response_attribute_name: String,
+}
+
+impl Default for UtoipaRule {
+    fn default() -> Self {
+        Self {
+            fn_attribute_name: "utoipa".to_string(),
+            schema_attribute_name: "ToSchema".to_string(),
+            response_attribute_name: "ToResponse".to_string(),
+        }
+    }
+}
+
+impl DiscoveryRule for UtoipaRule {
+    fn name(&self) -> String {
+        format!(
+            "utoipa (function_attribute_name = \"{}\", schema_attribute_name = \"{}\", response_attribute_name = \"{}\")",
+            self.fn_attribute_name, self.schema_attribute_name, self.response_attribute_name
+        )
+    }
+
+    fn classify(&self, item: &RuleItem<'_>) -> syn::Result<Vec<Classification>> {
+        let mut out = match item {
+            RuleItem::Fn(f) => match fn_matches(f, &self.fn_attribute_name) {
+                true => vec![Classification::Path],
+                false => vec![],
+            },
+            RuleItem::Struct(_) | RuleItem::Enum(_) => {
+                let derives = item.derives()?;
+                let mut out = self.parse_from_derives(&derives);
+                let attrs = item.attrs();
+                // `modifiers(...)` takes a value, only a unit struct is one
+                let unit = matches!(item, RuleItem::Struct(s) if matches!(s.fields, syn::Fields::Unit));
+                if unit && attrs.iter().any(|attr| is_utoipauto_attribute(attr, "modifier")) {
+                    out.push(Classification::Modifier);
+                }
+                // Only the marked documentation structs are nested, a plain `#[derive(OpenApi)]` keeps its handlers
+                if attrs.iter().any(|attr| is_utoipauto_attribute(attr, "nest")) {
+                    out.push(Classification::Nest);
+                }
+                out
+            }
+            RuleItem::Impl(im) => self.parse_from_impl(im).into_iter().collect(),
+        };
+        if is_ignored(item.attrs()) {
+            out.push(Classification::Ignore);
+        }
+        Ok(out)
+    }
+}
+
+impl UtoipaRule {
+    /// Search for ToSchema and ToResponse implementations in the derives
+    fn parse_from_derives(&self, derives: &[syn::Path]) -> Vec<Classification> {
+        let mut out: Vec<Classification> = vec![];
+        for path in derives {
+            if path.segments.len() == 2 && path.segments[0].ident == "utoipa" {
+                match path.segments[1].ident.to_string().as_str() {
+                    "ToSchema" => out.push(Classification::Schema),
+                    "ToResponse" => out.push(Classification::Response),
+                    _ => {}
+                }
+            } else {
+                if path.is_ident(&self.schema_attribute_name) {
+                    out.push(Classification::Schema);
+                }
+                if path.is_ident(&self.response_attribute_name) {
+                    out.push(Classification::Response);
+                }
+            }
+        }
+        out
+    }
+
+    fn parse_from_impl(&self, im: &ItemImpl) -> Option<Classification> {
+        im.trait_
+            .as_ref()
+            .and_then(|trt| trt.1.segments.last().map(|p| p.ident.to_string()))
+            .and_then(|impl_name| {
+                if impl_name.eq(self.schema_attribute_name.as_str()) {
+                    Some(Classification::Schema)
+                } else if impl_name.eq(self.response_attribute_name.as_str()) {
+                    Some(Classification::Response)
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+/// Classify the items marked with an attribute, a derive or an implemented trait
+///
+/// `MarkerRule::new(Classification::Schema, "ApiModel")` finds `#[derive(ApiModel)]`, `#[api_model]`
+/// and `impl ApiModel for X`. A marker without `::` also matches the last segment of a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkerRule {
+    pub classification: Classification,
+    pub marker: String,
+}
+
+impl MarkerRule {
+    pub fn new(classification: Classification, marker: impl Into<String>) -> Self {
+        Self {
+            classification,
+            marker: marker.into().replace(' ', ""),
+        }
+    }
+
+    /// Read the rules of a config file, see [`MarkerRule::parse_config`]
+    pub fn read_config(path: impl AsRef<Path>) -> io::Result<Vec<MarkerRule>> {
+        Self::parse_config(&fs::read_to_string(path)?)
+    }
+
+    /// Parse the rules of a config file, one `kind = path` rule per line
+    ///
+    /// ```
+    /// use utoipauto_core::rules::{Classification, MarkerRule};
+    ///
+    /// let rules = MarkerRule::parse_config(
+    ///     r#"
+    /// ## In-house macros expanding to utoipa ones
+    /// path = my_api::handler
+    /// schema = ApiModel
+    /// response = ApiResponse
+    /// ignore = internal_only
+    /// "#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(rules[1], MarkerRule::new(Classification::Schema, "ApiModel"));
+    /// ```
+    pub fn parse_config(content: &str) -> io::Result<Vec<MarkerRule>> {
+        let mut rules = vec![];
+        for (index, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", index + 1, message))
+            };
+            let (kind, marker) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected `kind = path`, found `{}`", line)))?;
+            let classification = match kind.trim() {
+                "path" => Classification::Path,
+                "schema" => Classification::Schema,
+                "response" => Classification::Response,
+                "modifier" => Classification::Modifier,
+                "nest" => Classification::Nest,
+                "ignore" => Classification::Ignore,
+                kind => {
+                    return Err(error(format!(
+                        "unknown kind `{}`, expected one of: path, schema, response, modifier, nest, ignore",
+                        kind
+                    )))
+                }
+            };
+            let marker = marker.trim();
+            if syn::parse_str::<syn::Path>(marker).is_err() {
+                return Err(error(format!("`{}` is not a rust path", marker)));
+            }
+            rules.push(MarkerRule::new(classification, marker));
+        }
+        Ok(rules)
+    }
+
+    fn matches(&self, path: &syn::Path) -> bool {
+        let path_string = path.to_token_stream().to_string().replace(' ', "");
+        path_string == self.marker
+            || (!self.marker.contains("::") && path.segments.last().is_some_and(|s| s.ident == self.marker))
+    }
+}
+
+impl DiscoveryRule for MarkerRule {
+    fn name(&self) -> String {
+        format!("{} = {}", self.classification, self.marker)
+    }
+
+    fn classify(&self, item: &RuleItem<'_>) -> syn::Result<Vec<Classification>> {
+        let attributes = item.attrs().iter().map(|attr| attr.path());
+        let derives = item.derives()?;
+        let matches = attributes
+            .chain(derives.iter())
+            .chain(item.trait_path())
+            .any(|path| self.matches(path));
+        Ok(match matches {
+            true => vec![self.classification],
+            false => vec![],
+        })
+    }
+}
+
+/// `true` if the function has the attribute we're looking for, even if it is ignored
+fn fn_matches(f: &ItemFn, fn_attributes_name: &str) -> bool {
+    f.attrs.iter().any(|attr| {
+        attr.meta
+            .path()
+            .segments
+            .iter()
+            .any(|segment| segment.ident == fn_attributes_name)
+    })
+}
+
+/// `#[utoipauto::<name>]` or `#[<name>]`, with or without arguments
+pub fn is_utoipauto_attribute(attr: &Attribute, name: &str) -> bool {
+    let path = attr.path();
+    path.is_ident(name)
+        || (path.segments.len() == 2 && path.segments[0].ident == "utoipauto" && path.segments[1].ident == name)
+}
+
+fn is_ignored(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if let Some(name) = attr.path().get_ident() {
+            name.eq("utoipa_ignore")
+        } else {
+            false
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+    use syn::ItemFn;
+
+    use super::{Classification, DiscoveryRule, MarkerRule, RuleItem, UtoipaRule};
+
+    #[test]
+    fn test_fn_matches() {
+        let item_fn: ItemFn = syn::parse2(quote! {
+            #[utoipa]
+            pub fn route_custom() {}
+        })
+        .unwrap();
+        assert!(super::fn_matches(&item_fn, "utoipa"));
+        assert!(!super::fn_matches(&item_fn, "handler"));
+
+        let item_fn: ItemFn = syn::parse2(quote! {
+            #[utoipa_ignore]
+            #[utoipa::path(get, path = "/route")]
+            pub fn route_custom() {}
+        })
+        .unwrap();
+        assert!(super::fn_matches(&item_fn, "path"));
+
+        let item_fn: ItemFn = syn::parse2(quote! {
+            pub fn route_custom() {}
+        })
+        .unwrap();
+        assert!(!super::fn_matches(&item_fn, "utoipa"));
+    }
+
+    #[test]
+    fn test_utoipa_rule() {
+        let rule = UtoipaRule::default();
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[derive(Debug, utoipa::ToSchema, ToResponse)]
+            #[utoipa_ignore]
+            pub struct Model;
+        };
+        assert_eq!(
+            rule.classify(&RuleItem::Struct(&item)).unwrap(),
+            vec![Classification::Schema, Classification::Response, Classification::Ignore]
+        );
+
+        let item: syn::ItemImpl = syn::parse_quote! {
+            impl utoipa::ToSchema for Model {}
+        };
+        assert_eq!(
+            rule.classify(&RuleItem::Impl(&item)).unwrap(),
+            vec![Classification::Schema]
+        );
+
+        // The modifiers are opt-in, and must be unit structs
+        let item: syn::ItemImpl = syn::parse_quote! {
+            impl utoipa::Modify for SecurityAddon {}
+        };
+        assert!(rule.classify(&RuleItem::Impl(&item)).unwrap().is_empty());
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[utoipauto::modifier(order = 1)]
+            pub struct SecurityAddon;
+        };
+        assert_eq!(
+            rule.classify(&RuleItem::Struct(&item)).unwrap(),
+            vec![Classification::Modifier]
+        );
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[utoipauto::modifier]
+            pub struct SecurityAddon {
+                scheme: String,
+            }
+        };
+        assert!(rule.classify(&RuleItem::Struct(&item)).unwrap().is_empty());
+
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[derive(OpenApi)]
+            pub struct UsersApi;
+        };
+        assert!(rule.classify(&RuleItem::Struct(&item)).unwrap().is_empty());
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[utoipauto::nest]
+            #[derive(OpenApi)]
+            pub struct UsersApi;
+        };
+        assert_eq!(
+            rule.classify(&RuleItem::Struct(&item)).unwrap(),
+            vec![Classification::Nest]
+        );
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[utoipauto(paths = "./src")]
+            #[derive(OpenApi)]
+            pub struct ApiDoc;
+        };
+        assert!(rule.classify(&RuleItem::Struct(&item)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_marker_rule() {
+        let rule = MarkerRule::new(Classification::Schema, "ApiModel");
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[derive(Debug, my_macros::ApiModel)]
+            pub struct Model;
+        };
+        assert_eq!(
+            rule.classify(&RuleItem::Struct(&item)).unwrap(),
+            vec![Classification::Schema]
+        );
+
+        let rule = MarkerRule::new(Classification::Path, "my_api::handler");
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[my_api::handler(get, "/")]
+            pub fn handler() {}
+        };
+        assert_eq!(rule.classify(&RuleItem::Fn(&item)).unwrap(), vec![Classification::Path]);
+        let item: syn::ItemFn = syn::parse_quote! {
+            #[other::handler]
+            pub fn handler() {}
+        };
+        assert!(rule.classify(&RuleItem::Fn(&item)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_config_errors() {
+        assert!(MarkerRule::parse_config("schema ApiModel").is_err());
+        assert!(MarkerRule::parse_config("model = ApiModel").is_err());
+        assert!(MarkerRule::parse_config("schema = not a path").is_err());
+        assert!(MarkerRule::parse_config("# only a comment\n\n").unwrap().is_empty());
+    }
+}
@@ -1,6 +1,11 @@
-use proc_macro2::TokenStream;
+use std::path::Path;
 
-use crate::{discover::discover_from_file, token_utils::Parameters};
+use crate::{
+    builder::{parse_registry, registry_path},
+    discover::{Discoverer, Discovery},
+    rules::MarkerRule,
+    token_utils::Parameters,
+};
 
 pub fn rem_first_and_last(value: &str) -> &str {
     let mut chars = value.chars();
@@ -25,165 +30,94 @@ pub fn trim_parentheses(str: &str) -> String {
     s
 }
 
-/// Extract the file paths from the attributes
-/// Support the old syntax (MODULE_TREE_PATH => MODULE_SRC_PATH) ; (MODULE_TREE_PATH => MODULE_SRC_PATH) ;
-/// and the new syntax MODULE_SRC_PATH, MODULE_SRC_PATH
+/// Find the candidate closest to `name`, to suggest a fix for a typo
 ///
 /// # Example
 /// ```
-/// use utoipauto_core::string_utils::extract_paths;
-/// let paths = extract_paths(
-///    "(utoipa_auto_macro::tests::controllers::controller1 => ./utoipa-auto-macro/tests/controllers/controller1.rs) ; (utoipa_auto_macro::tests::controllers::controller2 => ./utoipa-auto-macro/tests/controllers/controller2.rs)"
-/// );
-/// assert_eq!(
-///   paths,
-///  vec![
-///    "./utoipa-auto-macro/tests/controllers/controller1.rs".to_string(),
-///   "./utoipa-auto-macro/tests/controllers/controller2.rs".to_string(),
-/// ]   
-/// );
+/// use utoipauto_core::string_utils::closest_match;
+/// assert_eq!(closest_match("path", &["paths", "function_attribute_name"]), Some("paths"));
+/// assert_eq!(closest_match("something_else", &["paths"]), None);
 /// ```
-pub fn extract_paths(attributes: &str) -> Vec<String> {
-    let attributes = trim_parentheses(attributes);
-
-    if attributes.contains('|') {
-        panic!("Please use the new syntax ! paths=\"(MODULE_TREE_PATH => MODULE_SRC_PATH) ;\"")
-    }
-    let paths = if attributes.contains("=>") {
-        extract_paths_arrow(attributes)
-    } else {
-        extract_paths_coma(attributes)
-    };
-    if paths.is_empty() {
-        panic!("utoipauto: No paths specified !")
-    }
-    paths
+pub fn closest_match<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (levenshtein(name, candidate), *candidate))
+        // Only suggest names that are reasonably close to the given one
+        .filter(|(distance, candidate)| *distance <= candidate.len().max(name.len()) / 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
 }
 
-// (MODULE_TREE_PATH => MODULE_SRC_PATH) ; (MODULE_TREE_PATH => MODULE_SRC_PATH) ;
-// extract the paths from the string
-// Here for legacy support
-fn extract_paths_arrow(attributes: String) -> Vec<String> {
-    let mut paths: Vec<String> = vec![];
-    let attributes = attributes.split(';');
-
-    for p in attributes {
-        let pair = p.split_once("=>");
-
-        if let Some(pair) = pair {
-            paths.push(trim_whites(pair.1));
+/// Edit distance between two strings
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
         }
+        previous = current;
     }
-    paths
+    previous[b.len()]
 }
 
-// MODULE_SRC_PATH, MODULE_SRC_PATH
-fn extract_paths_coma(attributes: String) -> Vec<String> {
-    let mut paths: Vec<String> = vec![];
-    let attributes = attributes.split(',');
-
-    for p in attributes {
-        paths.push(trim_whites(p));
+/// Return every item found in the paths to explore: the functions with the #[utoipa] attribute,
+/// the structs with the #[derive(ToSchema)] attribute and the structs with the #[derive(ToResponse)] attribute,
+/// including the ones that are skipped
+///
+/// Every path is explored, so that all the errors can be reported at once.
+/// With `include = "..."`, the items are read from the registry file generated by a build script instead
+pub fn discover(params: &Parameters) -> syn::Result<Discovery> {
+    if params.registry {
+        // The items are registered at link time, there is nothing to explore
+        return Ok(Discovery::default());
     }
-    paths
-}
-
-/// Return the list of all the functions with the #[utoipa] attribute
-/// and the list of all the structs with the #[derive(ToSchema)] attribute
-/// and the list of all the structs with the #[derive(ToResponse)] attribute
-pub fn discover(paths: Vec<String>, params: &Parameters) -> (TokenStream, TokenStream, TokenStream) {
-    let mut uto_paths = Vec::new();
-    let mut uto_models = Vec::new();
-    let mut uto_responses = Vec::new();
-    for p in paths {
-        let path = extract_crate_name(p);
-        let (list_fn, list_model, list_reponse) = discover_from_file(path.paths, path.crate_name, params);
-        uto_paths.extend(list_fn);
-        uto_models.extend(list_model);
-        uto_responses.extend(list_reponse);
+    if let Some((file, span)) = &params.include {
+        let path = registry_path(Path::new(file));
+        let error = |e: &dyn std::fmt::Display| {
+            syn::Error::new(
+                *span,
+                format!("utoipauto: failed to include `{}`: {}", path.display(), e),
+            )
+        };
+        let content = std::fs::read_to_string(&path).map_err(|e| error(&e))?;
+        let items = parse_registry(&content).map_err(|e| error(&e))?;
+        return Ok(Discovery {
+            items,
+            ..Discovery::default()
+        });
     }
-    // We need to add a coma after each path
-    (
-        quote::quote!(#(#uto_paths),*),
-        quote::quote!(#(#uto_models),*),
-        quote::quote!(#(#uto_responses),*),
-    )
-}
-
-#[derive(Debug, PartialEq)]
-struct Path {
-    paths: String,
-    crate_name: String,
-}
-
-fn extract_crate_name(path: String) -> Path {
-    let mut path = path.split(" from ");
-    let paths = path.next().unwrap();
-    let crate_name = path.next().unwrap_or("crate").to_string();
-    Path {
-        paths: paths.to_string(),
-        crate_name,
+    let mut discoverer = Discoverer::from(params);
+    if let Some((path, span)) = &params.rules {
+        let rules = MarkerRule::read_config(path)
+            .map_err(|e| syn::Error::new(*span, format!("utoipauto: failed to read the rules `{}`: {}", path, e)))?;
+        discoverer = discoverer.rules(rules);
     }
+    discoverer.explore()
 }
 
 #[cfg(test)]
 mod test {
-    use crate::string_utils::extract_paths;
-
-    #[test]
-    fn test_extract_path() {
-        let paths = "./src";
-        let extracted = extract_paths(paths);
-        assert_eq!(extracted, vec!["./src".to_string()]);
-    }
-
-    #[test]
-    fn test_extract_crate_name() {
-        assert_eq!(
-            super::extract_crate_name(
-                "utoipa_auto_macro::from::controllers::controller1 from utoipa_auto_macro".to_string(),
-            ),
-            super::Path {
-                paths: "utoipa_auto_macro::from::controllers::controller1".to_string(),
-                crate_name: "utoipa_auto_macro".to_string()
-            }
-        );
-    }
+    use super::closest_match;
 
     #[test]
-    fn test_extract_crate_name_default() {
-        assert_eq!(
-            super::extract_crate_name("utoipa_auto_macro::from::controllers::controller1".to_string()),
-            super::Path {
-                paths: "utoipa_auto_macro::from::controllers::controller1".to_string(),
-                crate_name: "crate".to_string()
-            }
-        );
-    }
-
-    #[test]
-    fn test_extract_paths_arrow() {
-        assert_eq!(
-            super::extract_paths(
-                "(utoipa_auto_macro::tests::controllers::controller1 => ./utoipa-auto-macro/tests/controllers/controller1.rs) ; (utoipa_auto_macro::tests::controllers::controller2 => ./utoipa-auto-macro/tests/controllers/controller2.rs)"
-            ),
-            vec![
-                "./utoipa-auto-macro/tests/controllers/controller1.rs".to_string(),
-                "./utoipa-auto-macro/tests/controllers/controller2.rs".to_string()
-            ]
-        );
+    fn test_levenshtein() {
+        assert_eq!(super::levenshtein("paths", "paths"), 0);
+        assert_eq!(super::levenshtein("path", "paths"), 1);
+        assert_eq!(super::levenshtein("funtion", "function"), 1);
+        assert_eq!(super::levenshtein("", "abc"), 3);
     }
 
     #[test]
-    fn test_extract_paths_coma() {
+    fn test_closest_match() {
+        let candidates = ["paths", "function_attribute_name", "schema_attribute_name"];
+        assert_eq!(closest_match("path", &candidates), Some("paths"));
         assert_eq!(
-            super::extract_paths(
-                "./utoipa-auto-macro/tests/controllers/controller1.rs, ./utoipa-auto-macro/tests/controllers/controller2.rs"
-            ),
-            vec![
-                "./utoipa-auto-macro/tests/controllers/controller1.rs".to_string(),
-                "./utoipa-auto-macro/tests/controllers/controller2.rs".to_string()
-            ]
+            closest_match("funtion_attribute_name", &candidates),
+            Some("function_attribute_name")
         );
+        assert_eq!(closest_match("models", &candidates), None);
     }
 }
@@ -0,0 +1,274 @@
+//! `tags = "module"`: tag the discovered operations with the name of their module
+//!
+//! The tags are applied by a generated `utoipa::Modify`, which finds each operation with the `utoipa::Path` impl
+//! of its handler. The handlers with an explicit `tag` or `tags` in `#[utoipa::path]` are left untouched.
+//! The tags are also defined in `#[openapi(tags(...))]`, in the order of their modules and with their doc comments
+//! as description.
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::discover::{DiscoveredItem, DiscoveredKind, DiscoveredModule};
+use crate::modify::operation_slot;
+use crate::prefixes::documented_path;
+use crate::token_utils::Prefix;
+
+/// How the tag of an operation is derived from the module of its handler
+///
+/// `tags = { from = "module", depth = 2, case = "kebab" }` tags `crate::api::user_accounts::handlers::list`
+/// with `user-accounts`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleTags {
+    /// The depth of the module below the crate root, the module of the handler if `None` or if it is shallower
+    pub depth: Option<usize>,
+    pub case: Case,
+}
+
+/// The transform applied to the module name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// As written: `user_accounts`
+    Snake,
+    /// `user-accounts`
+    Kebab,
+    /// `User Accounts`
+    Title,
+    /// `UserAccounts`
+    Pascal,
+}
+
+impl Case {
+    /// The values of `case = "..."`
+    pub const CHOICES: &'static [(&'static str, Case)] = &[
+        ("snake", Case::Snake),
+        ("kebab", Case::Kebab),
+        ("title", Case::Title),
+        ("pascal", Case::Pascal),
+    ];
+
+    pub fn names() -> Vec<&'static str> {
+        Self::CHOICES.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn from_name(name: &str) -> Option<Case> {
+        Self::CHOICES.iter().find(|(n, _)| *n == name).map(|(_, case)| *case)
+    }
+
+    pub fn apply(self, name: &str) -> String {
+        let words = name.split('_').filter(|word| !word.is_empty());
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        };
+        match self {
+            Case::Snake => name.to_string(),
+            Case::Kebab => words.collect::<Vec<_>>().join("-"),
+            Case::Title => words.map(capitalize).collect::<Vec<_>>().join(" "),
+            Case::Pascal => words.map(capitalize).collect(),
+        }
+    }
+}
+
+impl Default for ModuleTags {
+    fn default() -> Self {
+        ModuleTags {
+            depth: None,
+            case: Case::Snake,
+        }
+    }
+}
+
+impl ModuleTags {
+    /// The tag of a handler, `None` for a handler at the root of its crate
+    pub fn tag(&self, handler: &syn::Path) -> Option<String> {
+        let module = self.module(handler)?;
+        let name = module.segments.last()?.ident.to_string();
+        Some(self.case.apply(name.trim_start_matches("r#")))
+    }
+
+    /// The module a handler is tagged with: `crate::api::user_accounts`
+    pub fn module(&self, handler: &syn::Path) -> Option<syn::Path> {
+        // Without the crate and the handler
+        let modules = handler.segments.len().checked_sub(2).filter(|modules| *modules > 0)?;
+        let depth = match self.depth {
+            Some(depth) if depth <= modules => depth,
+            _ => modules,
+        };
+        let mut module = handler.clone();
+        module.segments = handler.segments.iter().take(depth + 1).cloned().collect();
+        Some(module)
+    }
+}
+
+/// The `(name = "...", description = "...")` definition of every tag of the discovered handlers
+///
+/// The tags follow the declaration order of their modules, the description is the doc comment of the module. The
+/// tags named in `defined` are left out.
+pub fn tag_definitions(
+    config: &ModuleTags,
+    modules: &[DiscoveredModule],
+    items: &[&DiscoveredItem],
+    defined: &[String],
+) -> TokenStream {
+    let mut tags: Vec<(Option<usize>, String, Option<&str>)> = vec![];
+    for item in tagged_handlers(items) {
+        let Some(module) = config.module(&item.path) else {
+            continue;
+        };
+        let name = config.tag(&item.path).expect("a handler with a module has a tag");
+        if defined.contains(&name) || tags.iter().any(|(_, existing, _)| *existing == name) {
+            continue;
+        }
+        let module = module.to_token_stream().to_string();
+        let position = modules
+            .iter()
+            .position(|m| m.path.to_token_stream().to_string() == module);
+        let doc = position.and_then(|position| modules[position].doc.as_deref());
+        tags.push((position, name, doc));
+    }
+    // The modules that weren't explored come last
+    tags.sort_by_key(|(position, _, _)| position.unwrap_or(usize::MAX));
+
+    let tags = tags.into_iter().map(|(_, name, doc)| match doc {
+        Some(doc) => quote!((name = #name, description = #doc)),
+        None => quote!((name = #name)),
+    });
+    quote!(#(#tags),*)
+}
+
+/// The discovered handlers without a `tag` or `tags` of their own
+fn tagged_handlers<'a>(items: &'a [&'a DiscoveredItem]) -> impl Iterator<Item = &'a DiscoveredItem> {
+    items
+        .iter()
+        .copied()
+        .filter(|item| item.kind == DiscoveredKind::Path && item.is_discovered())
+        .filter(|item| !item.route.as_ref().is_some_and(|route| route.tagged))
+}
+
+/// The `utoipa::Modify` tagging the operations of the discovered handlers, named `modifier`
+pub fn tags_modifier(
+    modifier: &Ident,
+    config: &ModuleTags,
+    prefixes: &[Prefix],
+    items: &[&DiscoveredItem],
+) -> TokenStream {
+    let tagged = tagged_handlers(items).filter_map(|item| {
+        let tag = config.tag(&item.path)?;
+        let handler = crate::expression::path_struct(&item.path);
+        let path = documented_path(prefixes, item);
+        let cfg = item.cfg.as_ref().map(|cfg| quote!(#[cfg(#cfg)]));
+        Some(quote!(#cfg tag::<#handler>(openapi, #path, #tag);))
+    });
+    let operation = operation_slot(&quote!(item), &quote!(method));
+    quote!(
+        #[allow(non_camel_case_types)]
+        struct #modifier;
+
+        impl ::utoipa::Modify for #modifier {
+            fn modify(&self, openapi: &mut ::utoipa::openapi::OpenApi) {
+                fn tag<P: ::utoipa::Path>(openapi: &mut ::utoipa::openapi::OpenApi, path: &str, tag: &str) {
+                    let Some(item) = openapi.paths.paths.get_mut(path) else {
+                        return;
+                    };
+                    for method in P::methods() {
+                        if let Some(operation) = #operation {
+                            operation.tags = Some(::std::vec![tag.to_string()]);
+                        }
+                    }
+                    let tags = openapi.tags.get_or_insert_with(::std::vec::Vec::new);
+                    if !tags.iter().any(|existing| existing.name == tag) {
+                        tags.push(::utoipa::openapi::tag::Tag::new(tag));
+                    }
+                }
+
+                #(#tagged)*
+            }
+        }
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+
+    use super::{tag_definitions, tags_modifier, Case, ModuleTags};
+    use crate::discover::Discoverer;
+
+    #[test]
+    fn test_module_tag() {
+        let handler: syn::Path = syn::parse_quote!(crate::api::user_accounts::handlers::list);
+        let tags = |depth, case| ModuleTags { depth, case }.tag(&handler);
+        assert_eq!(tags(None, Case::Snake).as_deref(), Some("handlers"));
+        assert_eq!(tags(Some(2), Case::Kebab).as_deref(), Some("user-accounts"));
+        assert_eq!(tags(Some(2), Case::Title).as_deref(), Some("User Accounts"));
+        assert_eq!(tags(Some(2), Case::Pascal).as_deref(), Some("UserAccounts"));
+        assert_eq!(tags(Some(9), Case::Snake).as_deref(), Some("handlers"));
+        assert_eq!(tags(Some(1), Case::Snake).as_deref(), Some("api"));
+        assert_eq!(ModuleTags::default().tag(&syn::parse_quote!(crate::health)), None);
+    }
+
+    #[test]
+    fn test_tags_modifier() {
+        let file: syn::File = syn::parse_quote! {
+            #[utoipa::path(get, path = "/users")]
+            pub fn list_users() {}
+
+            #[utoipa::path(get, path = "/users/{id}", tag = "people")]
+            pub fn get_user() {}
+
+            #[cfg(feature = "admin")]
+            #[utoipa::path(delete, path = "/users/{id}")]
+            pub fn delete_user() {}
+        };
+        let items = Discoverer::new().file("./src/users.rs", file).discover().unwrap();
+        let items = items.iter().collect::<Vec<_>>();
+        let modifier = syn::parse_quote!(__utoipauto_tags_ApiDoc);
+        let code = tags_modifier(&modifier, &ModuleTags::default(), &[], &items).to_string();
+        assert!(code.contains(
+            &quote!(tag::<crate::users::__path_list_users>(
+                openapi,
+                &<crate::users::__path_list_users as ::utoipa::Path>::path(),
+                "users"
+            );)
+            .to_string()
+        ));
+        assert!(!code.contains("__path_get_user"));
+        // Each handler is tagged under its cfg predicate
+        assert!(code.contains("# [cfg (feature = \"admin\")] tag :: < crate :: users :: __path_delete_user >"));
+    }
+
+    #[test]
+    fn test_tag_definitions() {
+        let lib: syn::File = syn::parse_quote! {
+            mod users {
+                //! Manage the accounts
+
+                #[utoipa::path(get, path = "/users")]
+                pub fn list_users() {}
+            }
+
+            mod billing {
+                #[utoipa::path(get, path = "/invoices")]
+                pub fn list_invoices() {}
+            }
+        };
+        let discovery = Discoverer::new().file("./src/lib.rs", lib).explore().unwrap();
+        let items = discovery.items.iter().rev().collect::<Vec<_>>();
+        let tags = tag_definitions(&ModuleTags::default(), &discovery.modules, &items, &[]).to_string();
+        assert_eq!(
+            tags,
+            quote::quote!(
+                (name = "users", description = "Manage the accounts"),
+                (name = "billing")
+            )
+            .to_string()
+        );
+        let defined = ["users".to_string()];
+        let tags = tag_definitions(&ModuleTags::default(), &discovery.modules, &items, &defined).to_string();
+        assert_eq!(tags, quote::quote!((name = "billing")).to_string());
+    }
+}
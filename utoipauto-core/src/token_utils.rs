@@ -1,66 +1,645 @@
+use std::collections::HashSet;
+
 use proc_macro::TokenStream;
-use proc_macro2::Literal;
-use quote::quote;
-use syn::Attribute;
+use proc_macro2::Span;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{
+    braced, bracketed,
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    token, Ident, LitStr, Token,
+};
+
+use crate::references::Selection;
+use crate::router::RouterKind;
+use crate::string_utils::closest_match;
+use crate::tags::{Case, ModuleTags};
+
+/// Every parameter accepted by `#[utoipauto(...)]`
+pub const PARAMETERS: &[&str] = &[
+    "paths",
+    "function_attribute_name",
+    "schema_attribute_name",
+    "response_attribute_name",
+    "warnings",
+    "explain",
+    "rules",
+    "include",
+    "coverage",
+    "router",
+    "prefixes",
+    "discovery",
+    "import",
+    "tags",
+    "operation_id",
+    "known_types",
+    "schemas",
+    "responses",
+];
+
+/// The parameters that configure the exploration, which is done by the build script with `include`
+const EXPLORATION_PARAMETERS: &[&str] = &[
+    "paths",
+    "function_attribute_name",
+    "schema_attribute_name",
+    "response_attribute_name",
+    "rules",
+];
+
+/// The parameters that depend on the source files, which aren't read with `discovery = "registry"`
+const FILE_PARAMETERS: &[&str] = &[
+    "paths",
+    "function_attribute_name",
+    "schema_attribute_name",
+    "response_attribute_name",
+    "rules",
+    "include",
+    "explain",
+    "coverage",
+    "router",
+    "prefixes",
+    "tags",
+    "operation_id",
+    "known_types",
+    "schemas",
+    "responses",
+];
+
+/// Every key accepted inside a `{ path = "...", crate = "..." }` entry of `paths`
+const PATH_PARAMETERS: &[&str] = &["path", "crate"];
+
+/// Every key accepted inside `tags = { from = "module", depth = 2, case = "kebab" }`
+const TAGS_PARAMETERS: &[&str] = &["from", "depth", "case"];
 
+/// The parameters of the `#[utoipauto(...)]` macro
 pub struct Parameters {
-    pub paths: String,
+    pub paths: Vec<DiscoveryPath>,
     pub fn_attribute_name: String,
     pub schema_attribute_name: String,
     pub response_attribute_name: String,
+    /// `warnings = "off"` silences the warnings about skipped items and undocumented types
+    pub warnings: bool,
+    /// `explain = "crate::api::get_user"`: the path of the item to explain, and the span of the literal
+    pub explain: Option<(String, Span)>,
+    /// `rules = "./utoipauto.rules"`: a config file of extra discovery rules, and the span of the literal
+    pub rules: Option<(String, Span)>,
+    /// `include = "openapi_registry.rs"`: a registry file generated by a build script, and the span of the literal
+    pub include: Option<(String, Span)>,
+    /// `coverage = "warn"` warns about the route handlers that lack the function attribute
+    pub coverage: bool,
+    /// `router = "axum"`: the web framework to register the discovered handlers with
+    pub router: Option<RouterKind>,
+    /// `prefixes = { "crate::api::v1" = "/api/v1" }`: the route prefix of the handlers of each module
+    pub prefixes: Vec<Prefix>,
+    /// `discovery = "registry"`: merge the items registered with `#[utoipauto::register]` at runtime,
+    /// instead of exploring the source files
+    pub registry: bool,
+    /// `import = [billing]`: the crates whose manifest, exported with `export_manifest!()`, is added
+    pub import: Vec<Ident>,
+    /// `tags = "module"`: tag the discovered operations with the name of their module
+    pub tags: Option<ModuleTags>,
+    /// `operation_id = "module_path"`: qualify the default operation ids with the module of the handler
+    pub module_operation_ids: bool,
+    /// `known_types = ["Money"]`: the types referenced by the handlers that are documented without being discovered
+    pub known_types: Vec<String>,
+    /// `schemas = "referenced"`: the discovered schemas added to `components(schemas(...))`
+    pub schemas: Selection,
+    /// `responses = "referenced"`: the discovered responses added to `components(responses(...))`
+    pub responses: Selection,
+    /// The name of every parameter given, in order
+    pub given: Vec<Ident>,
+}
+
+/// The route prefix of the handlers of a module and its submodules
+///
+/// `"crate::api::v1" = "/api/v1"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prefix {
+    /// `crate::api::v1`
+    pub module: String,
+    /// `/api/v1`
+    pub prefix: String,
+}
+
+impl Prefix {
+    /// The prefix of the module that contains `path` the most closely, if any
+    pub fn find<'a>(prefixes: &'a [Prefix], path: &str) -> Option<&'a Prefix> {
+        prefixes
+            .iter()
+            .filter(|prefix| path.starts_with(&format!("{}::", prefix.module)))
+            .max_by_key(|prefix| prefix.module.len())
+    }
 }
 
-/// Extract the paths string attribute from the proc_macro::TokenStream
+/// A source path to explore, with the name of the crate it belongs to
 ///
-/// If none is specified, we use the default path "./src"
-pub fn extract_attributes(stream: proc_macro2::TokenStream) -> Parameters {
-    let paths = extract_attribute("paths", stream.clone());
-    let fn_attribute_name = extract_attribute("function_attribute_name", stream.clone());
-    let schema_attribute_name = extract_attribute("schema_attribute_name", stream.clone());
-    let response_attribute_name = extract_attribute("response_attribute_name", stream);
-    // if no paths specified, we use the default path "./src"
-    Parameters {
-        paths: paths.unwrap_or("./src".to_string()),
-        fn_attribute_name: fn_attribute_name.unwrap_or("utoipa".to_string()),
-        schema_attribute_name: schema_attribute_name.unwrap_or("ToSchema".to_string()),
-        response_attribute_name: response_attribute_name.unwrap_or("ToResponse".to_string()),
-    }
-}
-
-// extract the name = "" attributes from the proc_macro::TokenStream
-fn extract_attribute(name: &str, stream: proc_macro2::TokenStream) -> Option<String> {
-    let mut has_value = false;
-
-    for token in stream {
-        if has_value {
-            if let proc_macro2::TokenTree::Literal(lit) = token {
-                return Some(get_content(lit));
+/// `paths = ["./src/api", { path = "./models/src", crate = "models" }]`
+pub struct DiscoveryPath {
+    pub path: String,
+    pub crate_name: String,
+    /// Span of the path literal, used to report discovery errors
+    pub span: Span,
+}
+
+impl DiscoveryPath {
+    fn from_literal(lit: &LitStr, crate_name: String) -> syn::Result<Self> {
+        let path = lit.value().trim().to_string();
+        if path.is_empty() {
+            return Err(syn::Error::new(lit.span(), "utoipauto: No paths specified !"));
+        }
+        if path.contains("=>") || path.contains(" from ") || path.contains([',', ';', '|']) {
+            return Err(syn::Error::new(
+                lit.span(),
+                "utoipauto: a path literal must contain a single path, \
+                 use `paths = [\"./src/api\", { path = \"./models/src\", crate = \"models\" }]` instead",
+            ));
+        }
+        Ok(Self {
+            path,
+            crate_name,
+            span: lit.span(),
+        })
+    }
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self {
+            // if no paths specified, we use the default path "./src"
+            paths: vec![DiscoveryPath {
+                path: "./src".to_string(),
+                crate_name: "crate".to_string(),
+                span: Span::call_site(),
+            }],
+            fn_attribute_name: "utoipa".to_string(),
+            schema_attribute_name: "ToSchema".to_string(),
+            response_attribute_name: "ToResponse".to_string(),
+            warnings: true,
+            explain: None,
+            rules: None,
+            include: None,
+            coverage: false,
+            router: None,
+            prefixes: vec![],
+            registry: false,
+            import: vec![],
+            tags: None,
+            module_operation_ids: false,
+            known_types: vec![],
+            schemas: Selection::All,
+            responses: Selection::All,
+            given: vec![],
+        }
+    }
+}
+
+impl Parse for Parameters {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut params = Parameters::default();
+        let mut seen = HashSet::new();
+        let mut errors: Option<syn::Error> = None;
+        let mut registry_span = None;
+
+        while !input.is_empty() {
+            let key = input.call(Ident::parse_any)?;
+            input.parse::<Token![=]>()?;
+            let name = key.to_string();
+
+            if !PARAMETERS.contains(&name.as_str()) {
+                combine_error(&mut errors, unknown_key_error(&key, "utoipauto parameter", PARAMETERS));
+                // Skip the value so that the remaining parameters are still checked
+                input.parse::<proc_macro2::TokenTree>()?;
+            } else if !seen.insert(name.clone()) {
+                combine_error(
+                    &mut errors,
+                    syn::Error::new(key.span(), format!("utoipauto: duplicate parameter `{name}`")),
+                );
+                input.parse::<proc_macro2::TokenTree>()?;
+            } else {
+                params.given.push(key.clone());
+                match name.as_str() {
+                    "paths" => params.paths = parse_paths(input)?,
+                    "function_attribute_name" => params.fn_attribute_name = parse_name(input)?,
+                    "schema_attribute_name" => params.schema_attribute_name = parse_name(input)?,
+                    "response_attribute_name" => params.response_attribute_name = parse_name(input)?,
+                    "warnings" => params.warnings = parse_choice(input, &["on", "off"])? == "on",
+                    "explain" => params.explain = Some(parse_explain(input)?),
+                    "rules" => {
+                        let lit: LitStr = input.fork().parse()?;
+                        params.rules = Some((parse_name(input)?, lit.span()));
+                    }
+                    "coverage" => params.coverage = parse_choice(input, &["off", "warn"])? == "warn",
+                    "router" => {
+                        let lit: LitStr = input.fork().parse()?;
+                        let name = parse_choice(input, &RouterKind::names())?;
+                        params.router = Some(RouterKind::parse(&name, lit.span())?);
+                    }
+                    "prefixes" => params.prefixes = parse_prefixes(input)?,
+                    "discovery" => {
+                        let lit: LitStr = input.fork().parse()?;
+                        params.registry = parse_choice(input, &["files", "registry"])? == "registry";
+                        registry_span = Some(lit.span());
+                        if params.registry && !cfg!(feature = "registry") {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                "utoipauto: `discovery = \"registry\"` requires the `registry` feature of utoipauto",
+                            ));
+                        }
+                    }
+                    "import" => params.import = parse_import(input)?,
+                    "tags" => params.tags = Some(parse_tags(input)?),
+                    "known_types" => params.known_types = parse_known_types(input)?,
+                    "schemas" => params.schemas = parse_selection(input)?,
+                    "responses" => params.responses = parse_selection(input)?,
+                    "operation_id" => {
+                        params.module_operation_ids =
+                            parse_choice(input, &["function", "module_path"])? == "module_path"
+                    }
+                    "include" => {
+                        let lit: LitStr = input.fork().parse()?;
+                        params.include = Some((parse_name(input)?, lit.span()));
+                    }
+                    _ => unreachable!("every parameter in PARAMETERS is handled"),
+                }
+            }
+
+            if input.is_empty() {
+                break;
             }
+            input.parse::<Token![,]>()?;
         }
-        if let proc_macro2::TokenTree::Ident(ident) = token {
-            if ident.to_string().eq(name) {
-                has_value = true;
+
+        if let Some((_, span)) = &params.include {
+            for name in EXPLORATION_PARAMETERS.iter().filter(|name| seen.contains(**name)) {
+                combine_error(
+                    &mut errors,
+                    syn::Error::new(
+                        *span,
+                        format!(
+                            "utoipauto: `{name}` can't be used with `include`, give it to the build script instead"
+                        ),
+                    ),
+                );
+            }
+        }
+
+        if let (true, Some(span)) = (params.registry, registry_span) {
+            for name in FILE_PARAMETERS.iter().filter(|name| seen.contains(**name)) {
+                combine_error(
+                    &mut errors,
+                    syn::Error::new(
+                        span,
+                        format!("utoipauto: `{name}` can't be used with `discovery = \"registry\"`"),
+                    ),
+                );
             }
         }
+
+        errors.map_or(Ok(params), Err)
+    }
+}
+
+/// `paths = "./src"`, `paths = { path = "./src", crate = "my_crate" }` or `paths = [...]` of both
+fn parse_paths(input: ParseStream) -> syn::Result<Vec<DiscoveryPath>> {
+    if input.peek(token::Bracket) {
+        let content;
+        let brackets = bracketed!(content in input);
+        let paths = content
+            .parse_terminated(parse_path_entry, Token![,])?
+            .into_iter()
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return Err(syn::Error::new(brackets.span.join(), "utoipauto: No paths specified !"));
+        }
+        Ok(paths)
+    } else {
+        Ok(vec![parse_path_entry(input)?])
     }
-    None
 }
 
-fn get_content(lit: Literal) -> String {
-    let content = lit.to_string();
-    content[1..content.len() - 1].to_string()
+fn parse_path_entry(input: ParseStream) -> syn::Result<DiscoveryPath> {
+    if !input.peek(token::Brace) {
+        let lit: LitStr = input.parse()?;
+        return DiscoveryPath::from_literal(&lit, "crate".to_string());
+    }
+
+    let content;
+    let braces = braced!(content in input);
+    let mut path: Option<LitStr> = None;
+    let mut crate_name: Option<LitStr> = None;
+    while !content.is_empty() {
+        let key = content.call(Ident::parse_any)?;
+        content.parse::<Token![=]>()?;
+        let value: LitStr = content.parse()?;
+        let slot = match key.to_string().as_str() {
+            "path" => &mut path,
+            "crate" => &mut crate_name,
+            _ => return Err(unknown_key_error(&key, "paths entry key", PATH_PARAMETERS)),
+        };
+        if slot.replace(value).is_some() {
+            return Err(syn::Error::new(key.span(), format!("utoipauto: duplicate key `{key}`")));
+        }
+
+        if content.is_empty() {
+            break;
+        }
+        content.parse::<Token![,]>()?;
+    }
+
+    let path = path.ok_or_else(|| syn::Error::new(braces.span.join(), "utoipauto: missing `path = \"...\"`"))?;
+    let crate_name = crate_name.map_or("crate".to_string(), |c| c.value());
+    DiscoveryPath::from_literal(&path, crate_name)
+}
+
+/// `import = billing` or `import = [billing, shipping]`
+fn parse_import(input: ParseStream) -> syn::Result<Vec<Ident>> {
+    let crates = match input.peek(token::Bracket) {
+        true => {
+            let content;
+            bracketed!(content in input);
+            content
+                .parse_terminated(Ident::parse_any, Token![,])?
+                .into_iter()
+                .collect::<Vec<_>>()
+        }
+        false => vec![input.call(Ident::parse_any)?],
+    };
+    for (i, name) in crates.iter().enumerate() {
+        if crates[..i].contains(name) {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("utoipauto: `{name}` is imported twice"),
+            ));
+        }
+    }
+    Ok(crates)
+}
+
+/// `schemas = "referenced"`, `responses = "none"`
+fn parse_selection(input: ParseStream) -> syn::Result<Selection> {
+    let name = parse_choice(input, &Selection::names())?;
+    Ok(Selection::from_name(&name).expect("the name is one of the choices"))
+}
+
+/// `known_types = "Money"` or `known_types = ["Money", "chrono::Duration"]`
+fn parse_known_types(input: ParseStream) -> syn::Result<Vec<String>> {
+    let names = match input.peek(token::Bracket) {
+        true => {
+            let content;
+            bracketed!(content in input);
+            content
+                .parse_terminated(<LitStr as Parse>::parse, Token![,])?
+                .into_iter()
+                .collect::<Vec<_>>()
+        }
+        false => vec![input.parse::<LitStr>()?],
+    };
+    names
+        .iter()
+        .map(|name| match syn::parse_str::<syn::Path>(&name.value()) {
+            Ok(_) => Ok(name.value()),
+            Err(_) => Err(syn::Error::new(
+                name.span(),
+                format!("utoipauto: `{}` is not a rust path", name.value()),
+            )),
+        })
+        .collect()
+}
+
+/// `tags = "module"` or `tags = { from = "module", depth = 2, case = "kebab" }`
+fn parse_tags(input: ParseStream) -> syn::Result<ModuleTags> {
+    if !input.peek(token::Brace) {
+        parse_choice(input, &["module"])?;
+        return Ok(ModuleTags::default());
+    }
+
+    let content;
+    let braces = braced!(content in input);
+    let mut tags = ModuleTags::default();
+    let mut seen = HashSet::new();
+    while !content.is_empty() {
+        let key = content.call(Ident::parse_any)?;
+        content.parse::<Token![=]>()?;
+        let name = key.to_string();
+        if !TAGS_PARAMETERS.contains(&name.as_str()) {
+            return Err(unknown_key_error(&key, "tags key", TAGS_PARAMETERS));
+        }
+        if !seen.insert(name.clone()) {
+            return Err(syn::Error::new(key.span(), format!("utoipauto: duplicate key `{key}`")));
+        }
+        match name.as_str() {
+            "from" => {
+                parse_choice(&content, &["module"])?;
+            }
+            "depth" => {
+                let lit: syn::LitInt = content.parse()?;
+                let depth = lit.base10_parse::<usize>()?;
+                if depth == 0 {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "utoipauto: the depth starts at 1, the first module below the crate root",
+                    ));
+                }
+                tags.depth = Some(depth);
+            }
+            "case" => {
+                let case = parse_choice(&content, &Case::names())?;
+                tags.case = Case::from_name(&case).expect("the case is one of the choices");
+            }
+            _ => unreachable!("every key in TAGS_PARAMETERS is handled"),
+        }
+
+        if content.is_empty() {
+            break;
+        }
+        content.parse::<Token![,]>()?;
+    }
+    if !seen.contains("from") {
+        return Err(syn::Error::new(
+            braces.span.join(),
+            "utoipauto: missing `from = \"module\"`",
+        ));
+    }
+    Ok(tags)
+}
+
+/// `prefixes = { "crate::api::v1" = "/api/v1", "crate::admin" = "/admin" }`
+fn parse_prefixes(input: ParseStream) -> syn::Result<Vec<Prefix>> {
+    let content;
+    braced!(content in input);
+    let mut prefixes: Vec<Prefix> = vec![];
+    while !content.is_empty() {
+        let module: LitStr = content.parse()?;
+        content.parse::<Token![=]>()?;
+        let prefix: LitStr = content.parse()?;
+        if syn::parse_str::<syn::Path>(&module.value()).is_err() {
+            return Err(syn::Error::new(
+                module.span(),
+                "utoipauto: expected the path of a module, like \"crate::api::v1\"",
+            ));
+        }
+        if !prefix.value().starts_with('/') {
+            return Err(syn::Error::new(
+                prefix.span(),
+                "utoipauto: a route prefix must start with `/`",
+            ));
+        }
+        let name = module.value().replace(' ', "");
+        if prefixes.iter().any(|p| p.module == name) {
+            return Err(syn::Error::new(
+                module.span(),
+                format!("utoipauto: duplicate prefix for `{name}`"),
+            ));
+        }
+        prefixes.push(Prefix {
+            module: name,
+            prefix: prefix.value().trim_end_matches('/').to_string(),
+        });
+
+        if content.is_empty() {
+            break;
+        }
+        content.parse::<Token![,]>()?;
+    }
+    Ok(prefixes)
+}
+
+fn parse_name(input: ParseStream) -> syn::Result<String> {
+    let lit: LitStr = input.parse()?;
+    let value = lit.value();
+    if value.trim().is_empty() {
+        return Err(syn::Error::new(lit.span(), "utoipauto: expected a non-empty name"));
+    }
+    Ok(value)
+}
+
+/// `explain = "crate::api::get_user"`
+fn parse_explain(input: ParseStream) -> syn::Result<(String, Span)> {
+    let lit: LitStr = input.parse()?;
+    let path: syn::Path = lit.parse().map_err(|_| {
+        syn::Error::new(
+            lit.span(),
+            "utoipauto: expected the rust path of an item, like \"crate::api::get_user\"",
+        )
+    })?;
+    Ok((path.to_token_stream().to_string().replace(' ', ""), lit.span()))
+}
+
+/// A string literal that must be one of `choices`
+fn parse_choice(input: ParseStream, choices: &[&str]) -> syn::Result<String> {
+    let lit: LitStr = input.parse()?;
+    let value = lit.value();
+    if choices.contains(&value.as_str()) {
+        return Ok(value);
+    }
+    let expected = choices
+        .iter()
+        .map(|c| format!("\"{c}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let message = match closest_match(&value, choices) {
+        Some(suggestion) => format!("utoipauto: unknown value \"{value}\", did you mean \"{suggestion}\"?"),
+        None => format!("utoipauto: unknown value \"{value}\", expected one of: {expected}"),
+    };
+    Err(syn::Error::new(lit.span(), message))
+}
+
+fn unknown_key_error(key: &Ident, what: &str, expected: &[&str]) -> syn::Error {
+    let message = match closest_match(&key.to_string(), expected) {
+        Some(suggestion) => format!("utoipauto: unknown {what} `{key}`, did you mean `{suggestion}`?"),
+        None => format!(
+            "utoipauto: unknown {what} `{key}`, expected one of: {}",
+            expected.join(", ")
+        ),
+    };
+    syn::Error::new(key.span(), message)
+}
+
+/// Parse the parameters of the `#[utoipauto(...)]` macro
+///
+/// If no paths are specified, we use the default path "./src"
+pub fn extract_attributes(stream: proc_macro2::TokenStream) -> syn::Result<Parameters> {
+    syn::parse2(stream)
+}
+
+/// Check that a macro taking the parameters of `#[utoipauto(...)]` was only given the `allowed` ones
+pub fn check_parameters(params: &Parameters, allowed: &[&str], macro_name: &str) -> syn::Result<()> {
+    let mut errors: Option<syn::Error> = None;
+    for name in params
+        .given
+        .iter()
+        .filter(|name| !allowed.contains(&name.to_string().as_str()))
+    {
+        combine_error(
+            &mut errors,
+            syn::Error::new(
+                name.span(),
+                format!("utoipauto: `{name}` can't be used with `{macro_name}`"),
+            ),
+        );
+    }
+    errors.map_or(Ok(()), Err)
 }
 
 /// Check if the macro is placed before the #[derive] and #[openapi] attributes
-/// Otherwise, panic!
-pub fn check_macro_placement(attrs: Vec<Attribute>) {
-    if !attrs.iter().any(|elm| elm.path().is_ident("derive")) {
-        panic!("Please put utoipauto before #[derive] and #[openapi]");
+/// Otherwise, return an error pointing at the struct
+pub fn check_macro_placement(item: &syn::ItemStruct) -> syn::Result<()> {
+    let mut errors: Option<syn::Error> = None;
+    if !item.attrs.iter().any(|elm| elm.path().is_ident("derive")) {
+        combine_error(
+            &mut errors,
+            syn::Error::new_spanned(
+                &item.ident,
+                "Please put utoipauto before #[derive] and #[openapi]: no #[derive(OpenApi)] found after #[utoipauto]",
+            ),
+        );
     }
 
-    if !attrs.iter().any(|elm| elm.path().is_ident("openapi")) {
-        panic!("Please put utoipauto before #[derive] and #[openapi]");
+    if !item.attrs.iter().any(|elm| elm.path().is_ident("openapi")) {
+        combine_error(
+            &mut errors,
+            syn::Error::new_spanned(
+                &item.ident,
+                "Please put utoipauto before #[derive] and #[openapi]: no #[openapi] found after #[utoipauto]",
+            ),
+        );
     }
+
+    errors.map_or(Ok(()), Err)
+}
+
+/// Append an error to an optional accumulator, so that several errors can be reported at once
+pub fn combine_error(errors: &mut Option<syn::Error>, error: syn::Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+/// Emit a compiler warning pointing at `span`
+///
+/// Proc macros can't emit warnings on stable, so we use a deprecated item instead.
+/// The warning is only emitted when `cfg` is enabled, or disabled if `negate` is true
+pub fn warning(
+    message: &str,
+    span: Span,
+    cfg: Option<&proc_macro2::TokenStream>,
+    negate: bool,
+) -> proc_macro2::TokenStream {
+    let cfg = match (cfg, negate) {
+        (None, _) => None,
+        (Some(cfg), false) => Some(quote!(#[cfg(#cfg)])),
+        (Some(cfg), true) => Some(quote!(#[cfg(not(#cfg))])),
+    };
+    quote_spanned!(span=>
+        #cfg
+        const _: () = {
+            #[deprecated(note = #message)]
+            #[allow(non_camel_case_types)]
+            struct utoipauto_warning;
+            let _ = utoipauto_warning;
+        };
+    )
 }
 
 // Output the macro back to the compiler
@@ -76,57 +655,50 @@ pub fn output_macro(openapi_macro: syn::ItemStruct) -> proc_macro::TokenStream {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_get_content() {
-        let lit = Literal::string("p1");
-        let content = get_content(lit);
-        assert_eq!(content, "p1");
-    }
-
     #[test]
     fn test_extract_attributes() {
         let tokens = quote! {
             paths = "p1"
         };
 
-        let attributes = extract_attributes(tokens);
-        assert_eq!(attributes.paths, "p1")
+        let attributes = extract_attributes(tokens).unwrap();
+        assert_eq!(attributes.paths.len(), 1);
+        assert_eq!(attributes.paths[0].path, "p1");
+        assert_eq!(attributes.paths[0].crate_name, "crate");
     }
 
     #[test]
-    fn test_extract_attribute() {
-        let quote = quote! {
-            paths = "p1", thing = "thing", other = "other"
+    fn test_extract_attributes_array() {
+        let tokens = quote! {
+            paths = ["./src/api", { path = "./models/src", crate = "models" }]
         };
 
-        let attributes = extract_attribute("thing", quote).unwrap();
-        assert_eq!(attributes, "thing");
+        let attributes = extract_attributes(tokens).unwrap();
+        assert_eq!(attributes.paths.len(), 2);
+        assert_eq!(attributes.paths[0].path, "./src/api");
+        assert_eq!(attributes.paths[0].crate_name, "crate");
+        assert_eq!(attributes.paths[1].path, "./models/src");
+        assert_eq!(attributes.paths[1].crate_name, "models");
     }
 
     #[test]
-    fn test_extract_attribute_none() {
-        let quote = quote! {
-            paths = "p1", thing = "thing", other = "other"
+    fn test_extract_attributes_single_entry() {
+        let tokens = quote! {
+            paths = { path = "./models/src", crate = "models" }
         };
 
-        let attributes = extract_attribute("not_found", quote);
-        assert_eq!(attributes, None);
-    }
-
-    #[test]
-    fn test_extract_attribute_empty() {
-        let quote = quote! {};
-
-        let attributes = extract_attribute("thing", quote);
-        assert_eq!(attributes, None);
+        let attributes = extract_attributes(tokens).unwrap();
+        assert_eq!(attributes.paths.len(), 1);
+        assert_eq!(attributes.paths[0].crate_name, "models");
     }
 
     #[test]
     fn test_extract_attributes_empty() {
         let tokens = quote! {};
 
-        let attributes = extract_attributes(tokens);
-        assert_eq!(attributes.paths, "./src");
+        let attributes = extract_attributes(tokens).unwrap();
+        assert_eq!(attributes.paths.len(), 1);
+        assert_eq!(attributes.paths[0].path, "./src");
         assert_eq!(attributes.fn_attribute_name, "utoipa");
         assert_eq!(attributes.schema_attribute_name, "ToSchema");
         assert_eq!(attributes.response_attribute_name, "ToResponse");
@@ -138,10 +710,281 @@ mod tests {
             paths = "p1", function_attribute_name = "handler", schema_attribute_name = "Schema", response_attribute_name = "Response"
         };
 
-        let attributes = extract_attributes(tokens);
-        assert_eq!(attributes.paths, "p1");
+        let attributes = extract_attributes(tokens).unwrap();
+        assert_eq!(attributes.paths[0].path, "p1");
         assert_eq!(attributes.fn_attribute_name, "handler");
         assert_eq!(attributes.schema_attribute_name, "Schema");
         assert_eq!(attributes.response_attribute_name, "Response");
     }
+
+    #[test]
+    fn test_extract_attributes_warnings() {
+        assert!(extract_attributes(quote! {}).unwrap().warnings);
+        assert!(!extract_attributes(quote! { warnings = "off" }).unwrap().warnings);
+        assert!(extract_attributes(quote! { warnings = "no" }).is_err());
+    }
+
+    #[test]
+    fn test_extract_attributes_explain() {
+        let attributes = extract_attributes(quote! { explain = "crate :: api::get_user" }).unwrap();
+        assert_eq!(attributes.explain.unwrap().0, "crate::api::get_user");
+        assert!(extract_attributes(quote! { explain = "not a path" }).is_err());
+    }
+
+    #[test]
+    fn test_extract_attributes_coverage() {
+        assert!(!extract_attributes(quote! {}).unwrap().coverage);
+        assert!(extract_attributes(quote! { coverage = "warn" }).unwrap().coverage);
+        assert!(extract_attributes(quote! { coverage = "on" }).is_err());
+    }
+
+    #[test]
+    fn test_extract_attributes_router() {
+        let result = extract_attributes(quote! { router = "axum" });
+        match cfg!(feature = "axum") {
+            true => assert_eq!(result.unwrap().router, Some(RouterKind::Axum)),
+            false => assert_eq!(
+                result.err().unwrap().to_string(),
+                "utoipauto: `router = \"axum\"` requires the `axum` feature of utoipauto"
+            ),
+        }
+        assert!(extract_attributes(quote! { router = "warp" }).is_err());
+    }
+
+    #[test]
+    fn test_extract_attributes_prefixes() {
+        let attributes =
+            extract_attributes(quote! { prefixes = { "crate::api" = "/api/", "crate::api::v1" = "/api/v1" } }).unwrap();
+        assert_eq!(
+            attributes.prefixes,
+            vec![
+                Prefix {
+                    module: "crate::api".to_string(),
+                    prefix: "/api".to_string()
+                },
+                Prefix {
+                    module: "crate::api::v1".to_string(),
+                    prefix: "/api/v1".to_string()
+                },
+            ]
+        );
+        let prefix = Prefix::find(&attributes.prefixes, "crate::api::v1::users::list").unwrap();
+        assert_eq!(prefix.prefix, "/api/v1");
+        assert!(Prefix::find(&attributes.prefixes, "crate::apis::list").is_none());
+
+        assert!(extract_attributes(quote! { prefixes = { "crate::api" = "api" } }).is_err());
+        assert!(extract_attributes(quote! { prefixes = { "not a path" = "/api" } }).is_err());
+    }
+
+    #[test]
+    fn test_extract_attributes_include() {
+        let attributes = extract_attributes(quote! { include = "openapi_registry.rs" }).unwrap();
+        assert_eq!(attributes.include.unwrap().0, "openapi_registry.rs");
+
+        let error = extract_attributes(quote! { paths = "./src", include = "openapi_registry.rs" })
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "utoipauto: `paths` can't be used with `include`, give it to the build script instead"
+        );
+    }
+
+    #[test]
+    fn test_extract_attributes_discovery() {
+        assert!(!extract_attributes(quote! { discovery = "files" }).unwrap().registry);
+
+        let registry = extract_attributes(quote! { discovery = "registry" });
+        if cfg!(feature = "registry") {
+            assert!(registry.unwrap().registry);
+            let error = extract_attributes(quote! { discovery = "registry", paths = "./src" })
+                .err()
+                .unwrap();
+            assert_eq!(
+                error.to_string(),
+                "utoipauto: `paths` can't be used with `discovery = \"registry\"`"
+            );
+        } else {
+            assert_eq!(
+                registry.err().unwrap().to_string(),
+                "utoipauto: `discovery = \"registry\"` requires the `registry` feature of utoipauto"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_attributes_import() {
+        let attributes = extract_attributes(quote! { import = [billing, shipping], paths = "./src" }).unwrap();
+        let crates = attributes.import.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(crates, vec!["billing", "shipping"]);
+        let given = attributes.given.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(given, vec!["import", "paths"]);
+
+        assert_eq!(extract_attributes(quote! { import = billing }).unwrap().import.len(), 1);
+        assert_eq!(
+            extract_attributes(quote! { import = [billing, billing] })
+                .err()
+                .unwrap()
+                .to_string(),
+            "utoipauto: `billing` is imported twice"
+        );
+    }
+
+    #[test]
+    fn test_extract_attributes_tags() {
+        let attributes = extract_attributes(quote! { tags = "module" }).unwrap();
+        assert_eq!(attributes.tags, Some(ModuleTags::default()));
+
+        let attributes = extract_attributes(quote! { tags = { from = "module", depth = 2, case = "kebab" } }).unwrap();
+        assert_eq!(
+            attributes.tags,
+            Some(ModuleTags {
+                depth: Some(2),
+                case: Case::Kebab
+            })
+        );
+
+        let error = |tokens| extract_attributes(tokens).err().unwrap().to_string();
+        assert_eq!(
+            error(quote! { tags = { depth = 2 } }),
+            "utoipauto: missing `from = \"module\"`"
+        );
+        assert_eq!(
+            error(quote! { tags = { from = "module", depth = 0 } }),
+            "utoipauto: the depth starts at 1, the first module below the crate root"
+        );
+        assert_eq!(
+            error(quote! { tags = { from = "module", case = "kebap" } }),
+            "utoipauto: unknown value \"kebap\", did you mean \"kebab\"?"
+        );
+    }
+
+    #[test]
+    fn test_extract_attributes_operation_id() {
+        assert!(!extract_attributes(quote! {}).unwrap().module_operation_ids);
+        let attributes = extract_attributes(quote! { operation_id = "module_path" }).unwrap();
+        assert!(attributes.module_operation_ids);
+
+        let error = extract_attributes(quote! { operation_id = "module_paht" })
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "utoipauto: unknown value \"module_paht\", did you mean \"module_path\"?"
+        );
+    }
+
+    #[test]
+    fn test_extract_attributes_known_types() {
+        let attributes = extract_attributes(quote! { known_types = ["Money", "chrono::Duration"] }).unwrap();
+        assert_eq!(attributes.known_types, vec!["Money", "chrono::Duration"]);
+        let attributes = extract_attributes(quote! { known_types = "Money" }).unwrap();
+        assert_eq!(attributes.known_types, vec!["Money"]);
+
+        let error = extract_attributes(quote! { known_types = ["Money<"] }).err().unwrap();
+        assert_eq!(error.to_string(), "utoipauto: `Money<` is not a rust path");
+    }
+
+    #[test]
+    fn test_extract_attributes_selection() {
+        let attributes = extract_attributes(quote! { schemas = "referenced", responses = "none" }).unwrap();
+        assert_eq!(attributes.schemas, Selection::Referenced);
+        assert_eq!(attributes.responses, Selection::None);
+        let attributes = extract_attributes(quote! { paths = "./src" }).unwrap();
+        assert_eq!(attributes.schemas, Selection::All);
+
+        let error = extract_attributes(quote! { schemas = "referenced_only" })
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "utoipauto: unknown value \"referenced_only\", did you mean \"referenced\"?"
+        );
+    }
+
+    #[test]
+    fn test_extract_attributes_unknown_key() {
+        let tokens = quote! {
+            path = "./src/api"
+        };
+
+        let error = extract_attributes(tokens).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "utoipauto: unknown utoipauto parameter `path`, did you mean `paths`?"
+        );
+    }
+
+    #[test]
+    fn test_extract_attributes_reports_every_unknown_key() {
+        let tokens = quote! {
+            path = "./src/api", funtion_attribute_name = "handler"
+        };
+
+        let error = extract_attributes(tokens).err().unwrap();
+        let messages: Vec<String> = error.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "utoipauto: unknown utoipauto parameter `path`, did you mean `paths`?",
+                "utoipauto: unknown utoipauto parameter `funtion_attribute_name`, did you mean `function_attribute_name`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_attributes_duplicate_key() {
+        let tokens = quote! {
+            paths = "./src", paths = "./other"
+        };
+
+        assert!(extract_attributes(tokens).is_err());
+    }
+
+    #[test]
+    fn test_extract_attributes_unknown_path_key() {
+        let tokens = quote! {
+            paths = [{ path = "./models/src", crates = "models" }]
+        };
+
+        let error = extract_attributes(tokens).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "utoipauto: unknown paths entry key `crates`, did you mean `crate`?"
+        );
+    }
+
+    #[test]
+    fn test_extract_attributes_legacy_syntax() {
+        let tokens = quote! {
+            paths = "./src/api from crate::api"
+        };
+
+        assert!(extract_attributes(tokens).is_err());
+    }
+
+    #[test]
+    fn test_extract_attributes_no_paths() {
+        assert!(extract_attributes(quote! { paths = [] }).is_err());
+        assert!(extract_attributes(quote! { paths = "" }).is_err());
+    }
+
+    #[test]
+    fn test_check_macro_placement() {
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[derive(OpenApi)]
+            #[openapi]
+            pub struct ApiDoc;
+        };
+        assert!(check_macro_placement(&item).is_ok());
+    }
+
+    #[test]
+    fn test_check_macro_placement_reports_every_missing_attribute() {
+        let item: syn::ItemStruct = syn::parse_quote! {
+            pub struct ApiDoc;
+        };
+        let error = check_macro_placement(&item).unwrap_err();
+        assert_eq!(error.into_iter().count(), 2);
+    }
 }
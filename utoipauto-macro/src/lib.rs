@@ -1,41 +1,357 @@
-use attribute_utils::update_openapi_macro_attributes;
+use attribute_utils::{
+    add_openapi_modifiers, add_openapi_nests, prepend_openapi_modifiers, update_openapi_macro_attributes,
+};
 use proc_macro::TokenStream;
 
+use coverage::undocumented_warnings;
+use discover::{cfg_groups, explain_note, report, skipped_items_warnings, to_token_streams};
 use quote::quote;
-use string_utils::{discover, extract_paths};
+use string_utils::discover;
 use syn::parse_macro_input;
-use token_utils::{check_macro_placement, extract_attributes, output_macro};
-use utoipauto_core::{attribute_utils, string_utils, token_utils};
+use token_utils::{check_macro_placement, combine_error, extract_attributes};
+use utoipauto_core::{
+    attribute_utils, builder, cfg_items, coverage, discover, expression, manifest, modifiers, nest, operation_ids,
+    prefixes, references, register, route_utils, router, string_utils, tags, token_utils,
+};
 
 /// Macro to automatically discover all the functions with the #[utoipa] attribute
 /// And the struct deriving ToSchema and ToResponse
 #[proc_macro_attribute]
 pub fn utoipauto(
-    attributes: proc_macro::TokenStream, // #[utoipauto(paths = "(MODULE_TREE_PATH => MODULE_SRC_PATH) ;")]
+    attributes: proc_macro::TokenStream, // #[utoipauto(paths = ["MODULE_SRC_PATH", { path = "MODULE_SRC_PATH", crate = "CRATE_NAME" }])]
     item: proc_macro::TokenStream,       // #[openapi(paths = "")]
 ) -> proc_macro::TokenStream {
-    // (MODULE_TREE_PATH => MODULE_SRC_PATH) ; (MODULE_TREE_PATH => MODULE_SRC_PATH) ; ...
-    let params = extract_attributes(attributes.into());
-    // [(MODULE_TREE_PATH, MODULE_SRC_PATH)]
-    let paths: Vec<String> = extract_paths(&params.paths);
-
     // #[openapi(...)]
-    let mut openapi_macro = parse_macro_input!(item as syn::ItemStruct);
+    let openapi_macro = parse_macro_input!(item as syn::ItemStruct);
 
-    // Discover all the functions with the #[utoipa] attribute
-    let (uto_paths, uto_models, uto_responses) = discover(paths, &params);
+    match expand_utoipauto(attributes.into(), &openapi_macro, None) {
+        // Output the macro back to the compiler
+        Ok(code) => TokenStream::from(code),
+        // Keep the struct so that the errors don't cascade into "cannot find type" errors
+        Err(errors) => {
+            let errors = errors.to_compile_error();
+            TokenStream::from(quote!(
+                #openapi_macro
+                #errors
+            ))
+        }
+    }
+}
 
-    // extract the openapi macro attributes : #[openapi(openapi_macro_attibutes)]
-    let openapi_macro_attibutes = &mut openapi_macro.attrs;
+/// `imported` are the items of the manifests of `import = [...]`, once they are collected
+fn expand_utoipauto(
+    attributes: proc_macro2::TokenStream,
+    openapi_macro: &syn::ItemStruct,
+    imported: Option<Vec<discover::DiscoveredItem>>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut errors: Option<syn::Error> = None;
 
     // Check if the macro is placed before the #[derive] and #[openapi] attributes
-    check_macro_placement(openapi_macro_attibutes.clone());
+    if let Err(e) = check_macro_placement(openapi_macro) {
+        combine_error(&mut errors, e);
+    }
+
+    // paths = ["MODULE_SRC_PATH", { path = "MODULE_SRC_PATH", crate = "CRATE_NAME" }], ...
+    let discovered = extract_attributes(attributes.clone())
+        // Discover all the functions with the #[utoipa] attribute
+        .and_then(|params| Ok((discover(&params)?, params)));
+
+    let (discovery, params) = match (errors, discovered) {
+        (None, Ok(discovered)) => discovered,
+        (Some(mut errors), Err(e)) => {
+            errors.combine(e);
+            return Err(errors);
+        }
+        (Some(errors), Ok(_)) | (None, Err(errors)) => return Err(errors),
+    };
+    let mut discovery = discovery;
+    match imported {
+        Some(imported) => discovery.items.extend(imported),
+        // Collect the manifests of the imported crates first, they come back through `__import!`
+        None if !params.import.is_empty() => {
+            return Ok(manifest::import_call(
+                &[],
+                &params.import,
+                &attributes,
+                &quote!(#openapi_macro),
+            ))
+        }
+        None => {}
+    }
+
+    // A documentation marked with `#[utoipauto::nest]` doesn't nest itself, nor skip its own handlers
+    let nests_itself = |item: &discover::DiscoveredItem| {
+        item.kind == discover::DiscoveredKind::Nest
+            && item
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == openapi_macro.ident)
+    };
+    if discovery.items.iter().any(nests_itself) {
+        discovery.items.retain(|item| !nests_itself(item));
+        nest::exclude_nested_handlers(&mut discovery.items);
+    }
+
+    let mut code = proc_macro2::TokenStream::new();
+    // `modifiers(...)` only accepts an identifier in scope
+    let registered = quote::format_ident!("__utoipauto_registered_{}", openapi_macro.ident);
+    let cfg_modifier = quote::format_ident!("__utoipauto_cfg_{}", openapi_macro.ident);
+    let tags_modifier = quote::format_ident!("__utoipauto_tags_{}", openapi_macro.ident);
+    let prefixes_modifier = quote::format_ident!("__utoipauto_prefixes_{}", openapi_macro.ident);
+    let operation_ids_modifier = quote::format_ident!("__utoipauto_operation_ids_{}", openapi_macro.ident);
+    let discovered_modifiers = quote::format_ident!("__utoipauto_modifiers_{}", openapi_macro.ident);
+    if params.registry {
+        code.extend(quote!(use ::utoipauto::registry::Registered as #registered;));
+    }
+    let items = &discovery.items;
+    // The modifiers listed by hand are not added twice
+    let existing_modifiers = attribute_utils::openapi_modifiers(&openapi_macro.attrs)?;
+    let existing_nests = attribute_utils::openapi_nests(&openapi_macro.attrs)?;
+    let all_items = items.iter().collect::<Vec<_>>();
+    check_duplicates(&all_items, params.module_operation_ids, &params.prefixes)?;
+
+    // The items compiled unconditionally are listed in `#[openapi(...)]`, the ones of each cfg predicate in a hidden
+    // documentation struct compiled under the predicate
+    let mut groups = cfg_groups(items).into_iter();
+    let (_, unconditional) = groups.next().expect("the unconditional items come first");
+    let selected = references::selected_items(&unconditional, params.schemas, params.responses);
+    // The prefixed handlers are documented by the prefixes modifier
+    let unprefixed = |item: &&discover::DiscoveredItem| !prefixes::is_prefixed(&params.prefixes, item);
+    let (uto_paths, uto_models, uto_responses) = to_token_streams(selected.iter().copied().filter(unprefixed));
+    let mut openapi = openapi_macro.clone();
+    let uto_tags = match &params.tags {
+        Some(config) => tags::tag_definitions(config, &discovery.modules, &unconditional, &[]),
+        None => proc_macro2::TokenStream::new(),
+    };
+    update_openapi_macro_attributes(&mut openapi.attrs, &uto_paths, &uto_models, &uto_responses, &uto_tags)?;
+    add_openapi_nests(&mut openapi.attrs, &nest::nest_entries(&existing_nests, &unconditional))?;
+    let defined_tags = attribute_utils::openapi_tags(&openapi.attrs)?;
+    let mut documentations = vec![];
+    for (cfg, group) in groups {
+        let cfg = cfg.expect("only the first group is unconditional");
+        // An unconditional schema only referenced by the handlers of the predicate is listed with them
+        let candidates = unconditional.iter().chain(&group).copied().collect::<Vec<_>>();
+        let group_selected = references::selected_items(&candidates, params.schemas, params.responses)
+            .into_iter()
+            .filter(|item| !selected.iter().any(|other| std::ptr::eq(*item, *other)))
+            .filter(unprefixed);
+        let (uto_paths, uto_models, uto_responses) = to_token_streams(group_selected);
+        let uto_tags = match &params.tags {
+            Some(config) => tags::tag_definitions(config, &discovery.modules, &group, &defined_tags),
+            None => proc_macro2::TokenStream::new(),
+        };
+        let mut attrs = vec![attribute_utils::build_new_openapi_attributes(
+            syn::punctuated::Punctuated::new(),
+            &uto_paths,
+            &uto_models,
+            &uto_responses,
+            &uto_tags,
+        )?];
+        add_openapi_nests(&mut attrs, &nest::nest_entries(&existing_nests, &group))?;
+        documentations.extend(attrs.pop().map(|attr| (cfg, attr)));
+    }
+    // The prefixed handlers are in the documentation before the modifiers listed by hand run, and are tagged and
+    // renamed at their prefixed paths
+    if !params.prefixes.is_empty() {
+        code.extend(prefixes::prefixes_modifier(
+            &prefixes_modifier,
+            &params.prefixes,
+            &all_items,
+        ));
+        prepend_openapi_modifiers(&mut openapi.attrs, &quote!(&#prefixes_modifier))?;
+    }
+    if !documentations.is_empty() {
+        code.extend(cfg_items::cfg_documentations(&cfg_modifier, &documentations));
+        // The documentation is complete before the modifiers listed by hand run
+        prepend_openapi_modifiers(&mut openapi.attrs, &quote!(&#cfg_modifier))?;
+    }
 
-    // Update the openapi macro attributes with the newly discovered paths
-    update_openapi_macro_attributes(openapi_macro_attibutes, &uto_paths, &uto_models, &uto_responses);
+    if params.registry {
+        add_openapi_modifiers(&mut openapi.attrs, &quote!(&#registered))?;
+    }
+    if let Some(config) = &params.tags {
+        add_openapi_modifiers(&mut openapi.attrs, &quote!(&#tags_modifier))?;
+        code.extend(tags::tags_modifier(
+            &tags_modifier,
+            config,
+            &params.prefixes,
+            &all_items,
+        ));
+    }
+    if params.module_operation_ids {
+        add_openapi_modifiers(&mut openapi.attrs, &quote!(&#operation_ids_modifier))?;
+        code.extend(operation_ids::operation_ids_modifier(
+            &operation_ids_modifier,
+            &params.prefixes,
+            &all_items,
+        ));
+    }
+    // The discovered modifiers run last, on the complete documentation
+    if let Some(modifiers) = modifiers::discovered_modifiers(&discovered_modifiers, &existing_modifiers, &all_items) {
+        add_openapi_modifiers(&mut openapi.attrs, &quote!(&#discovered_modifiers))?;
+        code.extend(modifiers);
+    }
+    code.extend(quote!(#openapi));
+    if let Some(kind) = params.router {
+        code.extend(router::router(openapi_macro, kind, &all_items, &params.prefixes));
+    }
+    code.extend(report(openapi_macro, items));
+    if params.warnings {
+        code.extend(skipped_items_warnings(items));
+        code.extend(references::unresolved_references_warnings(items, &params.known_types));
+    }
+    code.extend(explain_note(&params, &discovery));
+    if params.coverage {
+        code.extend(undocumented_warnings(
+            &discovery.undocumented,
+            &params.fn_attribute_name,
+        ));
+    }
+    if let Some((file, _)) = &params.include {
+        // Recompile when the build script regenerates the registry
+        let path = builder::registry_path(std::path::Path::new(file)).display().to_string();
+        code.extend(quote!(
+            const _: &str = include_str!(#path);
+        ));
+    }
+
+    Ok(code)
+}
+
+/// The duplicate operation ids and routes of the discovered items that can be compiled together
+fn check_duplicates(
+    items: &[&discover::DiscoveredItem],
+    module_operation_ids: bool,
+    prefixes: &[token_utils::Prefix],
+) -> syn::Result<()> {
+    let mut errors: Option<syn::Error> = None;
+    let checks = [
+        operation_ids::check_operation_ids(items, module_operation_ids),
+        route_utils::check_routes(items, prefixes),
+    ];
+    for duplicates in checks.into_iter().filter_map(Result::err) {
+        combine_error(&mut errors, duplicates);
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+/// Register a handler, a schema or a response for `#[utoipauto(discovery = "registry")]`
+///
+/// The kind of a struct or an enum comes from its derives, or is given with `#[register(schema)]`,
+/// `#[register(response)]` or both
+#[proc_macro_attribute]
+pub fn register(attributes: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match register::register(attributes.into(), item.clone().into()) {
+        Ok(code) => TokenStream::from(code),
+        Err(error) => {
+            let error = TokenStream::from(error.to_compile_error());
+            item.into_iter().chain(error).collect()
+        }
+    }
+}
 
-    // Output the macro back to the compiler
-    output_macro(openapi_macro)
+/// The discovered items as a `utoipa::openapi::OpenApi` expression, to merge or nest into a documentation built at
+/// runtime
+///
+/// Takes the exploration parameters of `#[utoipauto(...)]`: `paths`, the attribute names, `rules`, `warnings` and
+/// `include`
+#[proc_macro]
+pub fn discover_openapi(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let expression = extract_attributes(input.into()).and_then(|params| {
+        token_utils::check_parameters(&params, expression::EXPRESSION_PARAMETERS, "discover_openapi!")?;
+        let discovery = discover(&params)?;
+        check_duplicates(&discovery.items.iter().collect::<Vec<_>>(), false, &[])?;
+        let expression = expression::openapi_expression(&discovery.items);
+        let warnings = match params.warnings {
+            true => skipped_items_warnings(&discovery.items),
+            false => proc_macro2::TokenStream::new(),
+        };
+        Ok(quote!({
+            #warnings
+            #expression
+        }))
+    });
+    TokenStream::from(expression.unwrap_or_else(|e| e.to_compile_error()))
+}
+
+/// Export the items discovered in a library, for the crates documenting it with `#[utoipauto(import = [...])]`
+///
+/// Takes the exploration parameters of `#[utoipauto(...)]`: `paths`, the attribute names, `rules` and `warnings`
+#[proc_macro]
+pub fn export_manifest(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let exported = extract_attributes(input.into()).and_then(|params| {
+        manifest::check_export_parameters(&params)?;
+        let discovery = discover(&params)?;
+        let mut code = manifest::export_manifest(&discovery.items);
+        if params.warnings {
+            code.extend(skipped_items_warnings(&discovery.items));
+        }
+        Ok(code)
+    });
+    TokenStream::from(exported.unwrap_or_else(|e| e.to_compile_error()))
+}
+
+/// Collect the manifests of `#[utoipauto(import = [...])]`, called by the manifest of each imported crate
+#[doc(hidden)]
+#[proc_macro]
+pub fn __import(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let import = parse_macro_input!(input as manifest::Import);
+    if !import.pending.is_empty() {
+        return TokenStream::from(manifest::import_call(
+            &import.imported,
+            &import.pending,
+            &import.attributes,
+            &import.item,
+        ));
+    }
+    let item = TokenStream::from(import.item.clone());
+    let openapi_macro = parse_macro_input!(item as syn::ItemStruct);
+    let expanded = import
+        .items()
+        .and_then(|items| expand_utoipauto(import.attributes.clone(), &openapi_macro, Some(items)));
+    match expanded {
+        Ok(code) => TokenStream::from(code),
+        Err(errors) => {
+            let errors = errors.to_compile_error();
+            TokenStream::from(quote!(
+                #openapi_macro
+                #errors
+            ))
+        }
+    }
+}
+
+/// Add a `utoipa::Modify` type to the `modifiers(...)` of the documentation
+///
+/// `#[utoipauto::modifier(order = 1)]` runs it after the modifiers of a lower `order`, 0 by default. The type must be a
+/// unit struct.
+#[proc_macro_attribute]
+pub fn modifier(attributes: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let checked = modifiers::modifier_arguments(attributes.into())
+        .and_then(|_| modifiers::check_modifier_item(item.clone().into()));
+    match checked {
+        Ok(_) => item,
+        Err(error) => {
+            let error = TokenStream::from(error.to_compile_error());
+            item.into_iter().chain(error).collect()
+        }
+    }
+}
+
+/// Nest a documentation struct into the `nest(...)` of the documentation, under `path`
+///
+/// `#[utoipauto::nest]` nests the struct under the name of its module, `#[utoipauto::nest(path = "/users")]` under
+/// `path`. The handlers of its module are left out of the `paths(...)` of the documentation.
+#[proc_macro_attribute]
+pub fn nest(attributes: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match nest::nest_arguments(attributes.into()) {
+        Ok(_) => item,
+        Err(error) => {
+            let error = TokenStream::from(error.to_compile_error());
+            item.into_iter().chain(error).collect()
+        }
+    }
 }
 
 /// Ignore the function from the auto discovery
@@ -49,6 +365,12 @@ pub fn utoipa_ignore(_attr: proc_macro::TokenStream, item: proc_macro::TokenStre
     TokenStream::from(code)
 }
 
+/// Allow a route handler to stay undocumented with `coverage = "warn"`
+#[proc_macro_attribute]
+pub fn utoipa_undocumented(_attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    item
+}
+
 /// Useless macro to test custom function attributes
 #[proc_macro_attribute]
 pub fn test_handler(_attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -1,5 +1,12 @@
 #![allow(dead_code)] // This code is used in the tests
 
+// The code generated by the macros refers to `::utoipauto`, even inside this crate
+extern crate self as utoipauto;
+
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod report;
+
 pub use utoipauto_macro::*;
 
 #[cfg(test)]
@@ -0,0 +1,82 @@
+//! The link time registry of `#[utoipauto::register]`
+//!
+//! Every registered handler, schema and response is collected by `inventory` when the binary is linked,
+//! `#[utoipauto(discovery = "registry")]` merges them into the documentation with the [`Registered`] modifier.
+
+use utoipa::openapi::{Components, OpenApi};
+
+#[doc(hidden)]
+pub use inventory;
+
+/// An item registered with `#[utoipauto::register]`
+pub struct Registration {
+    /// The rust path of the item, the registrations are merged in the order of their names
+    pub name: &'static str,
+    register: fn(&mut OpenApi),
+}
+
+inventory::collect!(Registration);
+
+impl Registration {
+    /// A handler documented with `#[utoipa::path]`, as the documentation listing it alone in `paths(...)`
+    ///
+    /// The derived documentation holds the schemas the handler references, without the internal API of utoipa.
+    pub const fn path<O: utoipa::OpenApi>(name: &'static str) -> Self {
+        Registration {
+            name,
+            register: register_path::<O>,
+        }
+    }
+
+    /// A type implementing `ToSchema`, with the schemas it references
+    pub const fn schema<S: utoipa::ToSchema>(name: &'static str) -> Self {
+        Registration {
+            name,
+            register: register_schema::<S>,
+        }
+    }
+
+    /// A type implementing `ToResponse`
+    pub const fn response<R: for<'r> utoipa::ToResponse<'r>>(name: &'static str) -> Self {
+        Registration {
+            name,
+            register: register_response::<R>,
+        }
+    }
+}
+
+/// The modifier merging every registration into the documentation
+///
+/// Added to `modifiers(...)` by `#[utoipauto(discovery = "registry")]`. Every documentation of the binary
+/// gets every registration.
+pub struct Registered;
+
+impl utoipa::Modify for Registered {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let mut registrations = inventory::iter::<Registration>().collect::<Vec<_>>();
+        // The link order isn't stable between builds
+        registrations.sort_by_key(|registration| registration.name);
+        for registration in registrations {
+            (registration.register)(openapi);
+        }
+    }
+}
+
+fn components(openapi: &mut OpenApi) -> &mut Components {
+    openapi.components.get_or_insert_with(Components::new)
+}
+
+fn register_path<O: utoipa::OpenApi>(openapi: &mut OpenApi) {
+    openapi.merge(O::openapi());
+}
+
+fn register_schema<S: utoipa::ToSchema>(openapi: &mut OpenApi) {
+    let mut schemas = vec![(S::name().to_string(), S::schema())];
+    S::schemas(&mut schemas);
+    components(openapi).schemas.extend(schemas);
+}
+
+fn register_response<R: for<'r> utoipa::ToResponse<'r>>(openapi: &mut OpenApi) {
+    let (name, response) = R::response();
+    components(openapi).responses.insert(name.to_string(), response);
+}
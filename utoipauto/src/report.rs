@@ -0,0 +1,102 @@
+//! What `#[utoipauto]` found while exploring the source files
+//!
+//! Every documentation struct gets a `UTOIPAUTO_REPORT` constant listing the discovered items,
+//! including the ones left out of the documentation:
+//!
+//! ```rust,ignore
+//! #[utoipauto(paths = "./src")]
+//! #[derive(OpenApi)]
+//! #[openapi]
+//! pub struct ApiDoc;
+//!
+//! for item in ApiDoc::UTOIPAUTO_REPORT {
+//!     println!("{item}");
+//! }
+//! ```
+
+use std::fmt;
+
+/// What a discovered item documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiscoveredKind {
+    /// A function with the `#[utoipa::path]` attribute
+    Path,
+    /// A type deriving or implementing `ToSchema`
+    Schema,
+    /// A type deriving or implementing `ToResponse`
+    Response,
+    /// A unit struct marked with `#[utoipauto::modifier]`
+    Modifier,
+    /// A documentation struct marked with `#[utoipauto::nest]`
+    Nest,
+}
+
+/// Why a discovered item is left out of the documentation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkipReason {
+    /// The item has type or const generic parameters
+    Generic,
+    /// The item is marked with `#[utoipa_ignore]`, or ignored by a discovery rule
+    Ignored,
+    /// The file of the item is never declared with `mod` by its parent module
+    Unreachable,
+    /// The handler or the modifier belongs to the nested documentation struct of its module
+    Nested,
+    /// The item is compiled under a disabled `#[cfg(...)]` predicate
+    Cfg(&'static str),
+}
+
+/// An item found by `#[utoipauto]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiscoveredItem {
+    pub kind: DiscoveredKind,
+    /// The rust path of the item: `crate::api::get_user`
+    pub path: &'static str,
+    /// The file the item was found in, relative to the workspace root
+    pub file: &'static str,
+    /// The line of the item in `file`, 0 if unknown
+    pub line: u32,
+    /// Why the item is left out of the documentation, `None` if it is documented
+    pub skip_reason: Option<SkipReason>,
+}
+
+impl DiscoveredItem {
+    /// `true` if the item is added to the documentation
+    pub const fn is_discovered(&self) -> bool {
+        self.skip_reason.is_none()
+    }
+}
+
+impl fmt::Display for DiscoveredKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoveredKind::Path => write!(f, "path"),
+            DiscoveredKind::Schema => write!(f, "schema"),
+            DiscoveredKind::Response => write!(f, "response"),
+            DiscoveredKind::Modifier => write!(f, "modifier"),
+            DiscoveredKind::Nest => write!(f, "nested documentation"),
+        }
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Generic => write!(f, "it has generic parameters"),
+            SkipReason::Ignored => write!(f, "it is ignored with #[utoipa_ignore] or a discovery rule"),
+            SkipReason::Unreachable => write!(f, "its module is never declared by its parent module"),
+            SkipReason::Nested => write!(f, "it belongs to the nested documentation of its module"),
+            SkipReason::Cfg(predicate) => write!(f, "it is only compiled with `cfg({})`", predicate),
+        }
+    }
+}
+
+impl fmt::Display for DiscoveredItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} `{}` ({}:{})", self.kind, self.path, self.file, self.line)?;
+        match &self.skip_reason {
+            Some(reason) => write!(f, " skipped because {}", reason),
+            None => Ok(()),
+        }
+    }
+}
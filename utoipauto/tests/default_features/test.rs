@@ -1,12 +1,16 @@
 use utoipa::OpenApi;
 
+use utoipauto::report::{DiscoveredItem, DiscoveredKind, SkipReason};
 use utoipauto::utoipauto;
 
 use crate::default_features::controllers;
 
 // Discover from multiple controllers
 #[utoipauto(
-    paths = "( crate::controllers::controller1 => ./utoipauto/tests/default_features/controllers/controller1.rs) ; ( crate::controllers::controller2 => ./utoipauto/tests/default_features/controllers/controller2.rs )"
+    paths = [
+        "./utoipauto/tests/default_features/controllers/controller1.rs",
+        "./utoipauto/tests/default_features/controllers/controller2.rs"
+    ]
 )]
 #[derive(OpenApi)]
 #[openapi(info(title = "Percentage API", version = "1.0.0"))]
@@ -18,9 +22,7 @@ fn test_path_import() {
 }
 
 /// Discover from a single controller
-#[utoipauto(
-    paths = "( crate::controllers::controller1 => ./utoipauto/tests/default_features/controllers/controller1.rs)"
-)]
+#[utoipauto(paths = "./utoipauto/tests/default_features/controllers/controller1.rs")]
 #[derive(OpenApi)]
 #[openapi(info(title = "Percentage API", version = "1.0.0"))]
 pub struct SingleControllerApiDocs {}
@@ -30,6 +32,29 @@ fn test_ignored_path() {
     assert_eq!(SingleControllerApiDocs::openapi().paths.paths.len(), 1)
 }
 
+#[test]
+fn test_report() {
+    assert_eq!(
+        SingleControllerApiDocs::UTOIPAUTO_REPORT,
+        &[
+            DiscoveredItem {
+                kind: DiscoveredKind::Path,
+                path: "crate::default_features::controllers::controller1::route1",
+                file: "./utoipauto/tests/default_features/controllers/controller1.rs",
+                line: 5,
+                skip_reason: None,
+            },
+            DiscoveredItem {
+                kind: DiscoveredKind::Path,
+                path: "crate::default_features::controllers::controller1::route_ignored",
+                file: "./utoipauto/tests/default_features/controllers/controller1.rs",
+                line: 9,
+                skip_reason: Some(SkipReason::Ignored),
+            },
+        ]
+    )
+}
+
 /// Discover with manual path
 #[utoipauto(paths = "./utoipauto/tests/default_features/controllers/controller1.rs")]
 #[derive(OpenApi)]
@@ -45,7 +70,7 @@ fn test_manual_path() {
 }
 
 /// Discover from a module root
-#[utoipauto(paths = "( crate::controllers => ./utoipauto/tests/default_features/controllers)")]
+#[utoipauto(paths = ["./utoipauto/tests/default_features/controllers"])]
 #[derive(OpenApi)]
 #[openapi(info(title = "Percentage API", version = "1.0.0"))]
 pub struct ModuleApiDocs {}
@@ -66,9 +91,12 @@ fn test_crate_import_path() {
     assert_eq!(CrateApiDocs::openapi().paths.paths.len(), 2)
 }
 
-// Discover from multiple controllers new syntax
+// Discover from multiple controllers with explicit crate names
 #[utoipauto(
-    paths = "./utoipauto/tests/default_features/controllers/controller1.rs, ./utoipauto/tests/default_features/controllers/controller2.rs"
+    paths = [
+        { path = "./utoipauto/tests/default_features/controllers/controller1.rs", crate = "crate" },
+        { path = "./utoipauto/tests/default_features/controllers/controller2.rs", crate = "crate" },
+    ]
 )]
 #[derive(OpenApi)]
 #[openapi(info(title = "Percentage API", version = "1.0.0"))]
@@ -128,3 +156,33 @@ pub struct CustomHandlerApiDocs {}
 fn test_custom_handler() {
     assert_eq!(CustomHandlerApiDocs::openapi().paths.paths.len(), 1)
 }
+
+/// Discover custom handler with a rules file
+#[utoipauto(
+    paths = "./utoipauto/tests/default_features/controllers/controller3.rs",
+    rules = "./utoipauto/tests/default_features/utoipauto.rules"
+)]
+#[derive(OpenApi)]
+#[openapi(info(title = "Percentage API", version = "1.0.0"))]
+pub struct RulesFileApiDocs {}
+
+#[test]
+fn test_rules_file() {
+    assert_eq!(RulesFileApiDocs::openapi().paths.paths.len(), 1)
+}
+
+#[test]
+fn test_discover_openapi_expression() {
+    let models = utoipauto::discover_openapi!(paths = "./utoipauto/tests/default_features/models.rs");
+    let expected = ModelsImportApiDocs::openapi().components.expect("no components");
+    let components = models.components.expect("no components");
+    assert!(components.schemas == expected.schemas);
+    assert!(components.responses == expected.responses);
+
+    let mut api = MultiControllerApiDocs::openapi();
+    api.merge(utoipauto::discover_openapi!(
+        paths = "./utoipauto/tests/default_features/controllers/controller3.rs",
+        function_attribute_name = "test_handler"
+    ));
+    assert_eq!(api.paths.paths.len(), 3);
+}